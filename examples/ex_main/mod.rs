@@ -51,6 +51,9 @@ enum Command {
 		/// Enable realtime mode
 		#[clap(long)]
 		realtime: bool,
+		/// Set random seed of the game
+		#[clap(long)]
+		seed: Option<u32>,
 	},
 	/// Run game Human vs Bot
 	Human {
@@ -90,6 +93,7 @@ pub(crate) fn main(mut bot: impl Player + DerefMut<Target = Bot> + Deref<Target
 			sc2_version,
 			save_replay,
 			realtime,
+			seed,
 		}) => run_vs_computer(
 			&mut bot,
 			Computer::new(race, difficulty.unwrap_or(Difficulty::VeryEasy), ai_build),
@@ -98,6 +102,7 @@ pub(crate) fn main(mut bot: impl Player + DerefMut<Target = Bot> + Deref<Target
 				sc2_version: sc2_version.as_deref(),
 				realtime,
 				save_replay_as: save_replay.as_deref(),
+				random_seed: seed,
 			},
 		),
 		Some(Command::Human {
@@ -118,6 +123,7 @@ pub(crate) fn main(mut bot: impl Player + DerefMut<Target = Bot> + Deref<Target
 				sc2_version: sc2_version.as_deref(),
 				realtime: true,
 				save_replay_as: save_replay.as_deref(),
+				random_seed: None,
 			},
 		),
 		None => run_ladder_game(
@@ -2,37 +2,76 @@
 
 use crate::{
 	bot::{Locked, Rl},
-	client::{SC2Result, WS},
+	client::{SC2Error, WS},
 };
 use protobuf::Message;
-use sc2_proto::sc2api::{Request, Response};
-use tungstenite::Message::Binary;
+use sc2_proto::sc2api::{Request, Response, Status};
+use std::{thread, time::Duration};
+use tungstenite::{connect, Message::Binary};
+
+const RECONNECT_ATTEMPTS: u32 = 5;
 
 /// SC2 API. Can be accessed through [`self.api()`](crate::bot::Bot::api).
-pub struct API(Rl<WS>);
+///
+/// All methods return [`SC2Error`] on failure.
+pub struct API {
+	ws: Rl<WS>,
+	url: String,
+}
 impl API {
-	pub(crate) fn new(ws: WS) -> API {
-		API(Rl::new(ws))
+	pub(crate) fn new(ws: WS, url: String) -> API {
+		API { ws: Rl::new(ws), url }
+	}
+
+	/// Tries to connect to the same websocket again, making at most `attempts` attempts
+	/// with 1 second between them. Replaces old connection on success.
+	///
+	/// Resuming the game is possible only if SC2 process is still running and still in game.
+	pub(crate) fn reconnect(&self, attempts: u32) -> Result<(), SC2Error> {
+		for attempt in 1..=attempts {
+			match connect(self.url.as_str()) {
+				Ok((ws, _)) => {
+					*self.ws.write_lock() = ws;
+					return Ok(());
+				}
+				Err(e) => debug!("Reconnect attempt {} failed: {}", attempt, e),
+			}
+			thread::sleep(Duration::from_secs(1));
+		}
+		Err(SC2Error::ConnectionClosed)
 	}
 
 	/// Sends request and returns a response.
-	pub fn send(&self, req: Request) -> SC2Result<Response> {
-		let mut ws = self.0.write_lock();
+	pub fn send(&self, req: Request) -> Result<Response, SC2Error> {
+		let mut ws = self.ws.write_lock();
 
 		ws.write_message(Binary(req.write_to_bytes()?))?;
 
 		let msg = ws.read_message()?;
 
-		let mut res = Response::new();
-		res.merge_from_bytes(msg.into_data().as_slice())?;
-		Ok(res)
+		parse_response(msg.into_data())
+	}
+
+	/// Sends request and returns a response. If `reconnect` is `true` and connection was lost,
+	/// [reconnects](Self::reconnect) to the game and sends request again.
+	pub(crate) fn send_with_reconnect(&self, req: Request, reconnect: bool) -> Result<Response, SC2Error> {
+		match self.send(req.clone()) {
+			Err(SC2Error::ConnectionClosed) if reconnect => {
+				debug!("Connection with the game was lost, reconnecting");
+				self.reconnect(RECONNECT_ATTEMPTS)?;
+				self.send(req)
+			}
+			res => res,
+		}
 	}
 
 	/// Sends request, waits for the response, but ignores it (useful when response is empty).
-	pub fn send_request(&self, req: Request) -> SC2Result<()> {
-		let mut ws = self.0.write_lock();
+	/// Errors in the response are still returned.
+	pub fn send_request(&self, req: Request) -> Result<(), SC2Error> {
+		let mut ws = self.ws.write_lock();
 		ws.write_message(Binary(req.write_to_bytes()?))?;
-		let _ = ws.read_message()?;
+		let msg = ws.read_message()?;
+		parse_response(msg.into_data())?;
 		Ok(())
 	}
 
@@ -41,18 +80,31 @@ impl API {
 	///
 	/// [`send`]: Self::send
 	/// [`send_request`]: Self::send_request
-	pub fn send_only(&self, req: Request) -> SC2Result<()> {
-		self.0.write_lock().write_message(Binary(req.write_to_bytes()?))?;
+	pub fn send_only(&self, req: Request) -> Result<(), SC2Error> {
+		self.ws
+			.write_lock()
+			.write_message(Binary(req.write_to_bytes()?))?;
 		Ok(())
 	}
 	/// Waits for a response (useful only after [`send_only`]).
 	///
 	/// [`send_only`]: Self::send_only
-	pub fn wait_response(&self) -> SC2Result<Response> {
-		let msg = self.0.write_lock().read_message()?;
+	pub fn wait_response(&self) -> Result<Response, SC2Error> {
+		let msg = self.ws.write_lock().read_message()?;
+
+		parse_response(msg.into_data())
+	}
+}
 
-		let mut res = Response::new();
-		res.merge_from_bytes(msg.into_data().as_slice())?;
+fn parse_response(data: Vec<u8>) -> Result<Response, SC2Error> {
+	let mut res = Response::new();
+	res.merge_from_bytes(data.as_slice())?;
+	let errors = res.get_error();
+	if errors.is_empty() {
 		Ok(res)
+	} else if res.get_status() == Status::ended {
+		Err(SC2Error::GameEnded)
+	} else {
+		Err(SC2Error::InvalidResponse(errors.join("; ")))
 	}
 }
@@ -4,7 +4,12 @@ use crate::{
 	action::{Action, ActionResult, Commander, Target},
 	api::API,
 	client::SC2Result,
-	consts::{RaceValues, FRAMES_PER_SECOND, INHIBITOR_IDS, RACE_VALUES, TECH_ALIAS, UNIT_ALIAS},
+	consts::{
+		RaceValues, ABILITY_COOLDOWNS, ABILITY_ENERGY_COSTS, ABILITY_UPGRADES, ALL_PRODUCERS,
+		BUILDING_FOOTPRINTS, DAMAGING_EFFECTS, EFFECT_RADIUS, FRAMES_PER_SECOND, INHIBITOR_IDS,
+		MINERAL_FIELD_IDS, RACE_VALUES, TECH_ALIAS, TECH_REQUIREMENTS, UNIT_ALIAS, VESPENE_GEYSER_IDS,
+		WARPGATE_ABILITIES,
+	},
 	debug::{DebugCommand, Debugger},
 	distance::*,
 	game_data::{Cost, GameData},
@@ -12,15 +17,20 @@ use crate::{
 	game_state::Effect,
 	game_state::{Alliance, GameState},
 	geometry::Point2,
-	ids::{AbilityId, EffectId, UnitTypeId, UpgradeId},
+	ids::{AbilityId, BuffId, EffectId, UnitTypeId, UpgradeId},
+	pixel_map::z_height,
 	player::Race,
 	ramp::{Ramp, Ramps},
+	score::Score,
+	snapshot::GameSnapshot,
+	spatial::{SpatialGrid, DEFAULT_CELL_SIZE},
 	unit::{DataForUnit, SharedUnitData, Unit},
 	units::{AllUnits, Units},
-	utils::{dbscan, range_query},
+	utils::{astar, dbscan, range_query},
 	FromProto, IntoProto,
 };
 use indexmap::IndexSet;
+use lazy_init::Lazy as LazyInit;
 use num_traits::ToPrimitive;
 use rand::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
@@ -28,13 +38,37 @@ use sc2_proto::{
 	query::{RequestQueryBuildingPlacement, RequestQueryPathing},
 	sc2api::Request,
 };
-use std::{fmt, hash::BuildHasherDefault, process::Child};
+use std::{
+	cmp::Reverse,
+	collections::VecDeque,
+	error::Error,
+	fmt, fs,
+	hash::BuildHasherDefault,
+	path::PathBuf,
+	process::Child,
+	time::{Duration, Instant},
+};
 
 type FxIndexSet<T> = IndexSet<T, BuildHasherDefault<FxHasher>>;
+type Tile = (usize, usize);
+
+const FRAMES_PER_MINUTE: u32 = (FRAMES_PER_SECOND * 60.0) as u32;
+
+// Returns general ability for specific one (e.g. `EffectBlink` for `EffectBlinkStalker`)
+fn general_ability(game_data: &GameData, ability: AbilityId) -> AbilityId {
+	game_data
+		.abilities
+		.get(&ability)
+		.and_then(|data| data.remaps_to_ability_id)
+		.unwrap_or(ability)
+}
 
 #[cfg(feature = "enemies_cache")]
 use crate::{consts::BURROWED_IDS, unit::DisplayType};
 
+#[cfg(feature = "debug")]
+use crate::debug::UnitValue;
+
 #[cfg(feature = "parking_lot")]
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 #[cfg(all(not(feature = "parking_lot"), feature = "rayon"))]
@@ -213,6 +247,44 @@ impl Default for PlacementOptions {
 	}
 }
 
+/// Thresholds used by [`enemy_rushed`](Bot::enemy_rushed), [`worker_rush_detected`](Bot::worker_rush_detected)
+/// and [`cannon_rush_detected`](Bot::cannon_rush_detected) heuristics.
+#[derive(Clone, Copy)]
+pub struct RushOptions {
+	/// Rush can be detected only before this in-game time in seconds. [Default: `300`]
+	pub time: f32,
+	/// Maximum distance from bot's start location where enemies are counted. [Default: `40`]
+	pub distance: f32,
+	/// Minimum supply of enemy army near bot's base to consider it a rush. [Default: `4`]
+	pub supply: f32,
+	/// Minimum number of enemy workers near bot's base to consider it a worker rush. [Default: `5`]
+	pub workers: usize,
+	/// Radius around bot's start location, considered as main base. [Default: `20`]
+	pub main_radius: f32,
+}
+impl Default for RushOptions {
+	fn default() -> Self {
+		Self {
+			time: 300.0,
+			distance: 40.0,
+			supply: 4.0,
+			workers: 5,
+			main_radius: 20.0,
+		}
+	}
+}
+
+/// Timing statistics of labeled code section, collected by [`profile_section`](Bot::profile_section).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SectionStats {
+	/// Number of times section was executed.
+	pub calls: u32,
+	/// Total time spent in section.
+	pub total: Duration,
+	/// Longest single execution of section.
+	pub max: Duration,
+}
+
 /// Options used to configure which units are counted.
 /// Constructed with [`counter`](Bot::counter) and [`enemy_counter`](Bot::enemy_counter) methods.
 #[derive(Clone, Copy)]
@@ -395,6 +467,9 @@ pub struct Bot {
 	/// Actual race of your bot.
 	pub race: Race,
 	/// Requested race of your opponent.
+	///
+	/// If opponent is random, it's replaced with actual race as soon as any of enemy units is scouted
+	/// (see [`RandomRaceDetected`](crate::Event::RandomRaceDetected) event).
 	pub enemy_race: Race,
 	/// Your in-game id.
 	pub player_id: u32,
@@ -433,9 +508,12 @@ pub struct Bot {
 	pub minerals: u32,
 	/// Amount of gas bot has.
 	pub vespene: u32,
-	/// Amount of supply used by army.
+	/// Amount of supply used by army (including units in production).
+	///
+	/// Supply values are taken from observation, so supply providers
+	/// (Overlords, Pylons, Supply Depots, ...) are never counted as supply-using.
 	pub supply_army: u32,
-	/// Amount of supply used by workers.
+	/// Amount of supply used by workers (including workers in production).
 	pub supply_workers: u32,
 	/// The supply limit.
 	pub supply_cap: u32,
@@ -443,9 +521,12 @@ pub struct Bot {
 	pub supply_used: u32,
 	/// Amount of free supply.
 	pub supply_left: u32,
-	/// Bot's starting location.
+	/// Bot's starting location (position of the first townhall).
 	pub start_location: Point2,
 	/// Opponent's starting location.
+	///
+	/// On maps with more than 2 start locations it's only a guess (first of possible ones),
+	/// use [`known_enemy_start`](Self::known_enemy_start) to get confirmed one.
 	pub enemy_start: Point2,
 	/// Bot's resource center on start location.
 	pub start_center: Point2,
@@ -457,14 +538,36 @@ pub struct Bot {
 	pub expansions: Vec<Expansion>,
 	max_cooldowns: Rw<FxHashMap<UnitTypeId, f32>>,
 	last_units_health: Rw<FxHashMap<u64, u32>>,
+	last_units_position: Rw<FxHashMap<u64, (Point2, u32)>>,
+	last_units_loop: u32,
 	/// Obstacles on map which block vision of ground units, but still pathable.
 	pub vision_blockers: Vec<Point2>,
 	/// Ramps on map.
 	pub ramps: Ramps,
+	/// Thresholds of rush detection heuristics, can be adjusted.
+	pub rush_options: RushOptions,
 	enemy_upgrades: Rw<FxHashSet<UpgradeId>>,
 	pub(crate) owned_tags: FxHashSet<u64>,
 	pub(crate) under_construction: FxHashSet<u64>,
 	pub(crate) available_frames: Rw<FxHashMap<u64, u32>>,
+	rng: Rl<StdRng>,
+	path_distances: FxHashMap<(Tile, Tile), Option<f32>>,
+	possible_enemy_starts: Vec<Point2>,
+	known_enemy_start: Option<Point2>,
+	pub(crate) leave_requested: bool,
+	ability_casts: FxHashMap<(u64, AbilityId), u32>,
+	pub(crate) step_time: f32,
+	pub(crate) profiling: bool,
+	profile: FxHashMap<String, SectionStats>,
+	spatial: LazyInit<SpatialGrid>,
+	attackers: FxHashMap<u64, u64>,
+	reserved_tiles: FxHashSet<Tile>,
+	apm_limit: Option<u32>,
+	pub(crate) game_version: String,
+	data_dir: PathBuf,
+	pub(crate) realtime: Option<bool>,
+	pub(crate) reconnect: bool,
+	action_log: VecDeque<(u32, u32)>,
 }
 
 impl Bot {
@@ -481,10 +584,844 @@ impl Bot {
 	pub fn set_game_step(&self, val: u32) {
 		self.game_step.set_locked(val);
 	}
+	/// Sets if game should be played in real time mode, overriding `realtime` option of runner.
+	/// Must be called before game is created (e.g. in bot's constructor), has no effect on ladder.
+	///
+	/// In real time mode game doesn't wait for the bot, so if [`on_step`] takes too long,
+	/// some frames are skipped. Also [`game_step`](Self::game_step) isn't used in this mode,
+	/// bot gets new observation as soon as it finishes previous step.
+	///
+	/// [`on_step`]: crate::Player::on_step
+	pub fn set_realtime(&mut self, realtime: bool) {
+		self.realtime = Some(realtime);
+	}
+	/// Sets if bot should try to reconnect to the game when connection is lost, instead of
+	/// ending it with [`Undecided`](crate::player::GameResult::Undecided) result.
+	/// Disabled by default.
+	///
+	/// When any in-game request (observation, actions, debug commands, step or initial game data)
+	/// fails because connection was closed, bot makes a few attempts to reconnect
+	/// and then sends the same request again.
+	/// This works only if SC2 process is still running and the game wasn't ended by disconnect.
+	pub fn set_reconnect(&mut self, reconnect: bool) {
+		self.reconnect = reconnect;
+	}
 	/// Returns current game step.
 	pub fn game_step(&self) -> u32 {
 		self.game_step.get_locked()
 	}
+	/// Returns duration of the previous [`on_step`](crate::Player::on_step) call in milliseconds.
+	pub fn step_time_ms(&self) -> f32 {
+		self.step_time
+	}
+	/// Enables or disables timing of sections executed with [`profile_section`](Self::profile_section).
+	///
+	/// When enabled, summary of collected timings is logged with `info` level
+	/// after [`on_end`](crate::Player::on_end). It can also be taken with
+	/// [`profile_summary`](Self::profile_summary) to print it anywhere.
+	pub fn enable_profiling(&mut self, enabled: bool) {
+		self.profiling = enabled;
+	}
+	/// Executes given closure and, if [profiling is enabled](Self::enable_profiling),
+	/// records time it took under given label.
+	///
+	/// Returns result of the closure.
+	pub fn profile_section<T, F>(&mut self, name: &str, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T,
+	{
+		if !self.profiling {
+			return f(self);
+		}
+
+		let start = Instant::now();
+		let result = f(self);
+		let elapsed = start.elapsed();
+
+		let stats = self.profile.entry(name.to_string()).or_default();
+		stats.calls += 1;
+		stats.total += elapsed;
+		stats.max = stats.max.max(elapsed);
+		result
+	}
+	/// Returns timing statistics collected by [`profile_section`](Self::profile_section) mapped to labels.
+	pub fn profile_sections(&self) -> &FxHashMap<String, SectionStats> {
+		&self.profile
+	}
+	/// Returns human-readable summary of collected timings, sections with bigger total time go first.
+	pub fn profile_summary(&self) -> String {
+		let mut sections = self.profile.iter().collect::<Vec<_>>();
+		sections.sort_by_key(|(_, stats)| Reverse(stats.total));
+		sections
+			.into_iter()
+			.map(|(name, stats)| {
+				let total = stats.total.as_secs_f32() * 1000.0;
+				format!(
+					"{}: {} calls, total {:.3} ms, avg {:.3} ms, max {:.3} ms\n",
+					name,
+					stats.calls,
+					total,
+					total / stats.calls as f32,
+					stats.max.as_secs_f32() * 1000.0,
+				)
+			})
+			.collect()
+	}
+	/// Seeds random number generator used by the library (e.g. in [`find_placement`]
+	/// with `random` option), so bot's decisions can be reproduced in test games.
+	///
+	/// This doesn't control randomness of the game itself, use `random_seed` option
+	/// of the runners for that. Also timing of the bot's code and realtime mode
+	/// still can make games non-deterministic.
+	///
+	/// [`find_placement`]: Self::find_placement
+	pub fn set_rng_seed(&mut self, seed: u64) {
+		*self.rng.write_lock() = StdRng::seed_from_u64(seed);
+	}
+	/// Returns name of the map bot playing on.
+	pub fn map_name(&self) -> &str {
+		&self.game_info.map_name
+	}
+	/// Returns version of the game client (e.g. `"4.10.1.75800"`).
+	pub fn game_version(&self) -> &str {
+		&self.game_version
+	}
+	/// Returns opponent id on ladder (passed with `--OpponentId`) or `None` if it wasn't given.
+	pub fn opponent_id(&self) -> Option<&str> {
+		if self.opponent_id.is_empty() {
+			None
+		} else {
+			Some(&self.opponent_id)
+		}
+	}
+	/// Sets directory where bot stores data between games, default is `data`
+	/// (ladder gives bots write access to `data` directory in their folder).
+	pub fn set_data_dir<P: Into<PathBuf>>(&mut self, dir: P) {
+		self.data_dir = dir.into();
+	}
+	/// Returns path of file with data about opponent with given id, located in bot's data directory.
+	pub fn opponent_data_path(&self, id: &str) -> PathBuf {
+		let id = id
+			.chars()
+			.map(|c| {
+				if c.is_ascii_alphanumeric() || c == '-' {
+					c
+				} else {
+					'_'
+				}
+			})
+			.collect::<String>();
+		self.data_dir.join(format!("opponent_{}.dat", id))
+	}
+	/// Reads data about opponent with given id saved in previous games
+	/// and parses it with given function (e.g. `serde_json::from_str`).
+	///
+	/// Returns `None` if there's no saved data about this opponent yet.
+	pub fn load_opponent_data<T, E, F>(&self, id: &str, parse: F) -> SC2Result<Option<T>>
+	where
+		E: Into<Box<dyn Error>>,
+		F: FnOnce(&str) -> Result<T, E>,
+	{
+		let path = self.opponent_data_path(id);
+		if !path.exists() {
+			return Ok(None);
+		}
+		let content = fs::read_to_string(path)?;
+		parse(&content).map(Some).map_err(Into::into)
+	}
+	/// Serializes given data about opponent with given function (e.g. `serde_json::to_string`)
+	/// and writes it to bot's data directory, so it can be loaded in the next games
+	/// with [`load_opponent_data`](Self::load_opponent_data).
+	pub fn save_opponent_data<T, E, F>(&self, id: &str, data: &T, serialize: F) -> SC2Result<()>
+	where
+		E: Into<Box<dyn Error>>,
+		F: FnOnce(&T) -> Result<String, E>,
+	{
+		let content = serialize(data).map_err(Into::into)?;
+		fs::create_dir_all(&self.data_dir)?;
+		fs::write(self.opponent_data_path(id), content)?;
+		Ok(())
+	}
+	/// Returns unit (own, enemy or neutral) with given tag or `None` if it's not present on current step.
+	///
+	/// Lookup takes constant time, since [`units.all`](AllUnits::all) is indexed by tags.
+	#[inline]
+	pub fn get_unit(&self, tag: u64) -> Option<&Unit> {
+		self.units.all.get(tag)
+	}
+	/// Returns units (own, enemy or neutral) with tags from given set, present on current step.
+	pub fn select_by_tags(&self, tags: &FxHashSet<u64>) -> Units {
+		self.units.all.filter_tags(tags)
+	}
+	/// Returns distance between units with given tags
+	/// or `None` if any of them isn't present on current step.
+	pub fn distance_between_tags(&self, a: u64, b: u64) -> Option<f32> {
+		Some(self.get_unit(a)?.distance(self.get_unit(b)?))
+	}
+	/// Returns lightweight summary of current game state (resources, supply, unit counts, upgrades),
+	/// which can be serialized with `serde` feature enabled.
+	///
+	/// List of units isn't included, use [`snapshot_with_units`](Self::snapshot_with_units) to get it.
+	pub fn snapshot(&self) -> GameSnapshot {
+		GameSnapshot::new(self, false)
+	}
+	/// The same as [`snapshot`](Self::snapshot), but also includes short summary of all visible units.
+	pub fn snapshot_with_units(&self) -> GameSnapshot {
+		GameSnapshot::new(self, true)
+	}
+	/// All bot's structures, shortcut for [`units.my.structures`](crate::units::PlayerUnits::structures).
+	#[inline]
+	pub fn structures(&self) -> &Units {
+		&self.units.my.structures
+	}
+	/// All bot's townhalls, shortcut for [`units.my.townhalls`](crate::units::PlayerUnits::townhalls).
+	#[inline]
+	pub fn townhalls(&self) -> &Units {
+		&self.units.my.townhalls
+	}
+	/// All bot's workers, shortcut for [`units.my.workers`](crate::units::PlayerUnits::workers).
+	#[inline]
+	pub fn workers(&self) -> &Units {
+		&self.units.my.workers
+	}
+	/// All bot's gas buildings, shortcut for [`units.my.gas_buildings`](crate::units::PlayerUnits::gas_buildings).
+	#[inline]
+	pub fn gas_buildings(&self) -> &Units {
+		&self.units.my.gas_buildings
+	}
+	/// All mineral fields on the map, shortcut for [`units.mineral_fields`](AllUnits::mineral_fields).
+	#[inline]
+	pub fn mineral_fields(&self) -> &Units {
+		&self.units.mineral_fields
+	}
+	/// All vespene geysers on the map, shortcut for [`units.vespene_geysers`](AllUnits::vespene_geysers).
+	#[inline]
+	pub fn vespene_geysers(&self) -> &Units {
+		&self.units.vespene_geysers
+	}
+	/// Destructable rocks and other trash, shortcut for [`units.destructables`](AllUnits::destructables).
+	#[inline]
+	pub fn destructibles(&self) -> &Units {
+		&self.units.destructables
+	}
+	/// All watchtowers on the map, shortcut for [`units.watchtowers`](AllUnits::watchtowers).
+	#[inline]
+	pub fn watch_towers(&self) -> &Units {
+		&self.units.watchtowers
+	}
+	/// Returns bot's ready townhalls which are almost mined out,
+	/// i.e. there're less than `2000` minerals left in mineral fields near them.
+	///
+	/// Note: Contents of resources are known only while they're in vision,
+	/// so it's reliable only for bases with townhall on them.
+	pub fn depleted_bases(&self) -> Vec<&Unit> {
+		self.units
+			.my
+			.townhalls
+			.iter()
+			.filter(|t| {
+				t.is_ready()
+					&& !t.is_flying()
+					&& self
+						.units
+						.mineral_fields
+						.iter()
+						.filter(|m| m.is_closer(10.0, *t))
+						.map(|m| m.mineral_contents().unwrap_or(0))
+						.sum::<u32>() < 2000
+			})
+			.collect()
+	}
+	/// Returns bot's ready landed townhall closest to given position
+	/// or `None` if there're no such townhalls.
+	pub fn closest_townhall(&self, to: Point2) -> Option<&Unit> {
+		self.units
+			.my
+			.townhalls
+			.iter()
+			.filter(|t| t.is_ready() && !t.is_flying())
+			.closest(to)
+	}
+	/// Returns bot's townhall given unit belongs to, i.e. the [closest](Self::closest_townhall) one.
+	pub fn base_for(&self, unit: &Unit) -> Option<&Unit> {
+		self.closest_townhall(unit.position())
+	}
+	/// Returns mineral field closest to given townhall.
+	pub fn closest_mineral_to(&self, townhall: &Unit) -> Option<&Unit> {
+		self.units.mineral_fields.closest(townhall)
+	}
+	/// Returns mineral field closest to given worker among minerals of its [base](Self::base_for).
+	pub fn closest_mineral_to_worker(&self, worker: &Unit) -> Option<&Unit> {
+		let base = self.base_for(worker)?;
+		self.units
+			.mineral_fields
+			.iter()
+			.filter(|m| m.is_closer(10.0, base))
+			.closest(worker)
+	}
+	/// Returns worker which can be taken to build something without harming economy,
+	/// closest to given position.
+	///
+	/// Only workers gathering minerals without cargo are considered,
+	/// so workers returning resources, building, fighting or mining gas are skipped.
+	/// Returns `None` if there're no such workers, so caller can wait for one.
+	///
+	/// Use [`available_builder_from`](Self::available_builder_from) to exclude workers
+	/// with some roles (e.g. scout).
+	pub fn available_builder(&self, near: Point2) -> Option<&Unit> {
+		self.available_builder_from(&self.units.my.workers, near)
+	}
+	/// The same as [`available_builder`](Self::available_builder), but chooses from given workers.
+	pub fn available_builder_from<'a>(&self, workers: &'a Units, near: Point2) -> Option<&'a Unit> {
+		let gas_buildings = &self.units.my.gas_buildings;
+		workers
+			.iter()
+			.filter(|u| {
+				u.is_gathering()
+					&& !u.is_carrying_resource()
+					&& match u.target_tag() {
+						Some(tag) => !gas_buildings.contains_tag(tag),
+						None => true,
+					}
+			})
+			.closest(near)
+	}
+	/// Sends starting workers to mine, each to a different mineral patch near start location.
+	///
+	/// Closest worker-patch pairs are assigned first, so total travel is small.
+	/// When there're more workers than patches, the rest are paired the same way again.
+	/// Should be called on the first step (e.g. in [`on_start`](crate::Player::on_start)).
+	pub fn split_workers_on_start(&mut self) {
+		let minerals = self.units.mineral_fields.closer(10.0, self.start_location);
+		let mut workers = self.units.my.workers.iter().collect::<Vec<_>>();
+		if minerals.is_empty() {
+			return;
+		}
+
+		while !workers.is_empty() {
+			let mut pairs = workers
+				.iter()
+				.flat_map(|w| minerals.iter().map(move |m| (w.distance_squared(m), *w, m)))
+				.collect::<Vec<_>>();
+			pairs.sort_by(|(a, _, _), (b, _, _)| a.partial_cmp(b).unwrap());
+
+			let mut used_workers = FxHashSet::default();
+			let mut used_minerals = FxHashSet::default();
+			for (_, worker, mineral) in pairs {
+				if used_workers.contains(&worker.tag()) || used_minerals.contains(&mineral.tag()) {
+					continue;
+				}
+				worker.gather(mineral.tag(), false);
+				used_workers.insert(worker.tag());
+				used_minerals.insert(mineral.tag());
+			}
+			workers.retain(|w| !used_workers.contains(&w.tag()));
+		}
+	}
+	/// Speed mining for given worker, should be called every step for workers it's applied to.
+	///
+	/// Instead of leaving acceleration to the game, worker is moved right to the edge
+	/// of mineral field (or townhall when returning) and then gather (return) command is queued,
+	/// so it doesn't slow down before the target. Gives about 5-10% more minerals per worker,
+	/// mostly on the far patches. Commands are given only when worker is close to its target,
+	/// so it can be applied selectively, e.g. only to workers at the main base.
+	///
+	/// Workers mining gas or not mining at all are ignored.
+	pub fn speedmine(&mut self, worker: &Unit) {
+		if worker.orders().len() != 1 {
+			return;
+		}
+		// Mineral to gather or `None` if worker should return resources
+		let (target, mineral_tag) = if worker.is_gathering() {
+			let mineral = match worker
+				.target_tag()
+				.and_then(|tag| self.units.mineral_fields.get(tag))
+			{
+				Some(mineral) => mineral,
+				None => return,
+			};
+			let townhall = match self.base_for(mineral) {
+				Some(townhall) => townhall,
+				None => return,
+			};
+			(
+				mineral
+					.position()
+					.towards(townhall.position(), mineral.radius() + worker.radius()),
+				Some(mineral.tag()),
+			)
+		} else if worker.is_returning() && worker.is_carrying_minerals() {
+			let townhall = match self.base_for(worker) {
+				Some(townhall) => townhall,
+				None => return,
+			};
+			(
+				townhall
+					.position()
+					.towards(worker.position(), townhall.radius() + worker.radius()),
+				None,
+			)
+		} else {
+			return;
+		};
+
+		let distance = worker.distance(target);
+		if distance > 0.75 && distance < 2.0 {
+			worker.move_to(Target::Pos(target), false);
+			match mineral_tag {
+				Some(tag) => worker.gather(tag, true),
+				None => worker.return_resource(true),
+			}
+		}
+	}
+	/// Returns enemy combat units within `radius` of any bot's townhall,
+	/// sorted by how deep they are in bot's territory (closest to start location go first).
+	///
+	/// Workers, hallucinations and snapshots aren't considered threats.
+	pub fn threats_to_base(&self, radius: f32) -> Units {
+		let townhalls = &self.units.my.townhalls;
+		if townhalls.is_empty() {
+			return Units::new();
+		}
+		self.units
+			.enemy
+			.units
+			.filter(|u| {
+				u.can_attack()
+					&& !u.is_worker()
+					&& !u.is_hallucination()
+					&& !u.is_snapshot()
+					&& townhalls.iter().any(|t| t.is_closer(radius, *u))
+			})
+			.sorted_by_distance(self.start_location)
+	}
+	/// Returns bot's townhall which is threatened by enemies, i.e. the one closest
+	/// to the deepest of [`threats_to_base`](Self::threats_to_base) in `15` range.
+	pub fn base_under_attack(&self) -> Option<&Unit> {
+		let threats = self.threats_to_base(15.0);
+		let threat = threats.first()?;
+		self.units.my.townhalls.closest(threat)
+	}
+	/// Estimates income of minerals and vespene per minute (of game time) from worker assignments.
+	///
+	/// Assumptions:
+	/// - worker on minerals brings about `60` minerals per minute while there're at most 2 workers
+	///   per patch (townhall's [`ideal_harvesters`](Unit::ideal_harvesters)),
+	///   third worker on patch brings only about `25`, workers above that bring nothing
+	/// - each worker on gas brings about `38` vespene per minute, up to 3 workers per gas building
+	///   (i.e. `114` per saturated gas)
+	/// - rich resources, MULEs and distance to resources aren't taken into account
+	///
+	/// Returns `(minerals, vespene)`. Use [`collection_rate`](Self::collection_rate)
+	/// to get actual rate reported by the game.
+	pub fn income_rate(&self) -> (f32, f32) {
+		const MINERALS_PER_WORKER: f32 = 60.0;
+		const MINERALS_PER_EXTRA_WORKER: f32 = 25.0;
+		const VESPENE_PER_WORKER: f32 = 38.0;
+
+		let minerals = self
+			.units
+			.my
+			.townhalls
+			.iter()
+			.filter(|t| t.is_ready())
+			.map(|t| {
+				let assigned = t.assigned_harvesters().unwrap_or(0);
+				let ideal = t.ideal_harvesters().unwrap_or(0);
+				let extra = assigned.saturating_sub(ideal).min(ideal / 2);
+				assigned.min(ideal) as f32 * MINERALS_PER_WORKER + extra as f32 * MINERALS_PER_EXTRA_WORKER
+			})
+			.sum();
+		let vespene = self
+			.units
+			.my
+			.gas_buildings
+			.iter()
+			.filter(|g| g.is_ready() && g.vespene_contents().unwrap_or(0) > 0)
+			.map(|g| g.assigned_harvesters().unwrap_or(0).min(3) as f32 * VESPENE_PER_WORKER)
+			.sum();
+		(minerals, vespene)
+	}
+	/// Returns detailed score of the game on current step (collected resources, killed and lost value, ...).
+	pub fn score(&self) -> &Score {
+		&self.state.observation.score
+	}
+	/// Returns collection rate of minerals and vespene (per minute) reported by the game in score,
+	/// as `(minerals, vespene)`.
+	pub fn collection_rate(&self) -> (f32, f32) {
+		let score = &self.state.observation.score;
+		(score.collection_rate_minerals, score.collection_rate_vespene)
+	}
+	/// Keeps exactly `target_per_gas` workers on each of bot's ready gas buildings.
+	///
+	/// Missing workers are pulled from minerals (closest ones, preferring not carrying anything),
+	/// excess workers are sent back to mineral field closest to the gas building near a ready townhall.
+	/// Workers on their way to gas building are counted as assigned,
+	/// so calling it every step doesn't cause thrashing. Depleted gas buildings are skipped.
+	///
+	/// Returns net number of workers moved (positive if workers were pulled to gas,
+	/// negative if more of them were returned to minerals).
+	pub fn manage_gas_workers(&mut self, target_per_gas: usize) -> i32 {
+		let mut moved = 0;
+		let mut used = FxHashSet::default();
+		let workers = &self.units.my.workers;
+		let minerals = &self.units.mineral_fields;
+		let townhalls = self.units.my.townhalls.ready();
+
+		for gas in self
+			.units
+			.my
+			.gas_buildings
+			.iter()
+			.filter(|g| g.is_ready() && g.vespene_contents().unwrap_or(0) > 0)
+		{
+			// Assigned harvesters count is updated only when worker reaches gas building
+			let heading = workers
+				.iter()
+				.filter(|w| w.target_tag() == Some(gas.tag()))
+				.count();
+			let assigned = (gas.assigned_harvesters().unwrap_or(0) as usize).max(heading);
+			if assigned < target_per_gas {
+				let mut candidates = workers
+					.iter()
+					.filter(|w| {
+						!used.contains(&w.tag())
+							&& !w.is_carrying_vespene()
+							&& match w.target_tag() {
+								Some(tag) => w.is_gathering() && minerals.contains_tag(tag),
+								None => w.is_idle(),
+							}
+					})
+					.collect::<Vec<_>>();
+				candidates.sort_by(|a, b| {
+					a.is_carrying_minerals()
+						.cmp(&b.is_carrying_minerals())
+						.then_with(|| {
+							a.distance_squared(gas)
+								.partial_cmp(&b.distance_squared(gas))
+								.unwrap()
+						})
+				});
+				for w in candidates.into_iter().take(target_per_gas - assigned) {
+					w.gather(gas.tag(), false);
+					used.insert(w.tag());
+					moved += 1;
+				}
+			} else if assigned > target_per_gas {
+				let mineral = match minerals
+					.iter()
+					.filter(|m| townhalls.iter().any(|t| t.is_closer(10.0, *m)))
+					.closest(gas)
+				{
+					Some(mineral) => mineral.tag(),
+					None => continue,
+				};
+				let mut candidates = workers
+					.iter()
+					.filter(|w| !used.contains(&w.tag()) && w.target_tag() == Some(gas.tag()))
+					.collect::<Vec<_>>();
+				candidates.sort_by_key(|w| w.is_carrying_vespene());
+				for w in candidates.into_iter().take(assigned - target_per_gas) {
+					w.gather(mineral, false);
+					used.insert(w.tag());
+					moved -= 1;
+				}
+			}
+		}
+		moved
+	}
+	/// Returns bot's combat units, the same as [`select_army(false)`](Self::select_army).
+	pub fn army_units(&self) -> Units {
+		self.select_army(false)
+	}
+	/// Returns bot's army units.
+	///
+	/// Workers, larvae, eggs, cocoons, overlords, MULEs, changelings and structures (including Bunkers)
+	/// are never included. Units that can attack (Queens too) are always included,
+	/// as well as combat units without regular weapons
+	/// (Widow Mines, Disruptors, Carriers, Swarm Hosts, Infestors, High Templars, Vipers).
+	/// Other units (Observers, Overseers, Medivacs, Warp Prisms, Ravens, ...)
+	/// are included only if `include_support` is `true`.
+	pub fn select_army(&self, include_support: bool) -> Units {
+		self.units.my.units.filter(|u| match u.type_id() {
+			UnitTypeId::SCV
+			| UnitTypeId::Probe
+			| UnitTypeId::Drone
+			| UnitTypeId::MULE
+			| UnitTypeId::Larva
+			| UnitTypeId::Egg
+			| UnitTypeId::Overlord
+			| UnitTypeId::OverlordTransport
+			| UnitTypeId::OverlordCocoon
+			| UnitTypeId::TransportOverlordCocoon
+			| UnitTypeId::BanelingCocoon
+			| UnitTypeId::RavagerCocoon
+			| UnitTypeId::LurkerMPEgg
+			| UnitTypeId::BroodLordCocoon
+			| UnitTypeId::Changeling
+			| UnitTypeId::ChangelingMarine
+			| UnitTypeId::ChangelingMarineShield
+			| UnitTypeId::ChangelingZealot
+			| UnitTypeId::ChangelingZergling
+			| UnitTypeId::ChangelingZerglingWings => false,
+
+			UnitTypeId::WidowMine
+			| UnitTypeId::WidowMineBurrowed
+			| UnitTypeId::Disruptor
+			| UnitTypeId::Carrier
+			| UnitTypeId::SwarmHostMP
+			| UnitTypeId::SwarmHostBurrowedMP
+			| UnitTypeId::Infestor
+			| UnitTypeId::InfestorBurrowed
+			| UnitTypeId::HighTemplar
+			| UnitTypeId::Viper
+			| UnitTypeId::BanelingBurrowed => true,
+
+			_ => include_support || u.can_attack(),
+		})
+	}
+	/// Checks if any of given units is able to attack air targets.
+	///
+	/// Dual-purpose units (Thors, Cyclones, Stalkers, Queens, ...) and units
+	/// with weapons missing in game data (Battlecruisers, Sentries, Void Rays, ...) are handled correctly.
+	pub fn can_hit_air(&self, units: &Units) -> bool {
+		units.iter().any(|u| u.can_attack_air())
+	}
+	/// Returns bot's units and structures that can attack air targets.
+	pub fn anti_air_units(&self) -> Units {
+		let my = &self.units.my;
+		my.units
+			.iter()
+			.chain(my.structures.iter())
+			.filter(|u| u.can_attack_air())
+			.cloned()
+			.collect()
+	}
+	/// Returns bot's units and structures that can attack ground targets.
+	pub fn anti_ground_units(&self) -> Units {
+		let my = &self.units.my;
+		my.units
+			.iter()
+			.chain(my.structures.iter())
+			.filter(|u| u.can_attack_ground())
+			.cloned()
+			.collect()
+	}
+	/// Returns total supply of known enemy units (hallucinations aren't counted).
+	///
+	/// Units are taken from [`units.enemy`](AllUnits::enemy), or from `units.cached`
+	/// with `enemies_cache` feature (so units remembered from previous steps are counted too).
+	/// Since whole enemy army is rarely seen, this is only a lower bound.
+	pub fn enemy_known_supply(&self) -> u32 {
+		let enemies = {
+			#[cfg(not(feature = "enemies_cache"))]
+			{
+				&self.units.enemy.units
+			}
+			#[cfg(feature = "enemies_cache")]
+			{
+				&self.units.cached.units
+			}
+		};
+		enemies
+			.iter()
+			.filter(|u| !u.is_hallucination())
+			.map(|u| u.supply_cost())
+			.sum::<f32>() as u32
+	}
+	/// Returns known enemy units and structures (both ready and in-progress) counted by type.
+	///
+	/// Based on [`enemies_current`](Self::enemies_current) and [`enemies_ordered`](Self::enemies_ordered),
+	/// so like [`enemy_known_supply`](Self::enemy_known_supply) it's only a lower bound.
+	pub fn enemy_composition(&self) -> FxHashMap<UnitTypeId, usize> {
+		let mut composition = self.enemies_current.clone();
+		for (unit_type, count) in &self.enemies_ordered {
+			*composition.entry(*unit_type).or_default() += count;
+		}
+		composition
+	}
+	/// Checks if actual race of opponent is known,
+	/// i.e. opponent isn't random or it's race was already detected.
+	#[inline]
+	pub fn enemy_race_confirmed(&self) -> bool {
+		!self.enemy_race.is_random()
+	}
+	/// Returns all possible start locations of opponent (bot's own start location isn't included),
+	/// shortcut for [`game_info.start_locations`](GameInfo::start_locations).
+	///
+	/// See also [`possible_enemy_starts`](Self::possible_enemy_starts)
+	/// which excludes locations that were scouted.
+	#[inline]
+	pub fn enemy_start_locations(&self) -> &[Point2] {
+		&self.game_info.start_locations
+	}
+	/// Returns center of the playable area of the map,
+	/// shortcut for [`game_info.map_center`](GameInfo::map_center).
+	#[inline]
+	pub fn map_center(&self) -> Point2 {
+		self.game_info.map_center
+	}
+	/// Returns opponent's start location if it was confirmed by scouting enemy townhall there
+	/// or `None` if it's still unknown.
+	pub fn known_enemy_start(&self) -> Option<Point2> {
+		self.known_enemy_start
+	}
+	/// Returns start locations where opponent can be.
+	///
+	/// Locations which were seen without enemy townhall on them are eliminated,
+	/// and once enemy start is [confirmed](Self::known_enemy_start) only it is left.
+	pub fn possible_enemy_starts(&self) -> &[Point2] {
+		&self.possible_enemy_starts
+	}
+	/// Returns ramp leading out of bot's main base or `None` if it wasn't found.
+	pub fn main_base_ramp(&self) -> Option<&Ramp> {
+		Some(&self.ramps.my).filter(|r| !r.points.is_empty())
+	}
+	/// Returns ramp closest to bot's natural expansion or `None` if it wasn't found.
+	pub fn natural_ramp(&self) -> Option<&Ramp> {
+		Some(&self.ramps.natural).filter(|r| !r.points.is_empty())
+	}
+	/// Returns choke (ramp) closest to given position or `None` if no ramps were found.
+	///
+	/// Only ramps from [`ramps.all`](Ramps::all) are considered chokes.
+	pub fn closest_choke(&self, to: Point2) -> Option<&Ramp> {
+		self.ramps
+			.all
+			.iter()
+			.filter_map(|r| Some((r, r.center()?.distance_squared(to))))
+			.min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
+			.map(|(r, _)| r)
+	}
+	/// Returns first choke (ramp) army would go through moving from `a` to `b`,
+	/// or `None` if there's no choke on the route.
+	///
+	/// Route is approximated by straight line, ramp is considered to be on it
+	/// if any of its points is within 2 tiles of the line.
+	pub fn choke_between(&self, a: Point2, b: Point2) -> Option<&Ramp> {
+		let ab = b - a;
+		let len_squared = ab.len_squared();
+		let distance_to_route = |p: Point2| {
+			if len_squared == 0.0 {
+				return p.distance(a);
+			}
+			let ap = p - a;
+			let t = ((ap.x * ab.x + ap.y * ab.y) / len_squared).clamp(0.0, 1.0);
+			p.distance(a + ab * t)
+		};
+		self.ramps
+			.all
+			.iter()
+			.filter(|r| {
+				r.points
+					.iter()
+					.any(|p| distance_to_route(Point2::from(*p)) <= 2.0)
+			})
+			.filter_map(|r| Some((r, r.center()?.distance_squared(a))))
+			.min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap())
+			.map(|(r, _)| r)
+	}
+	/// Returns safe gathering position for bot's army.
+	///
+	/// If natural expansion is taken it's a position just inside of the [natural ramp](Self::natural_ramp),
+	/// otherwise it's a position on top of the [main ramp](Self::main_base_ramp).
+	/// If ramps weren't found, falls back to position near start location toward the map center.
+	pub fn rally_point(&self) -> Point2 {
+		if let Some(natural) = self.expansions.get(1).filter(|exp| exp.alliance.is_mine()) {
+			if let Some(center) = self
+				.natural_ramp()
+				.and_then(|r| r.points.iter().map(|p| Point2::from(*p)).center())
+			{
+				return center.towards(natural.loc, 4.0);
+			}
+		}
+		if let Some(top) = self.main_base_ramp().and_then(|r| r.top_center()) {
+			return Point2::from(top).towards(self.start_location, 3.0);
+		}
+		self.start_location.towards(self.map_center(), 8.0)
+	}
+	/// Sets rally point of production building, so new units will go to given position.
+	pub fn set_rally(&mut self, building: &Unit, at: Point2) {
+		building.command(AbilityId::RallyUnits, Target::Pos(at), false);
+	}
+	/// Returns visible enemy structures, which are closer to bot's start location
+	/// than to any of possible enemy start locations (i.e. proxies).
+	pub fn proxy_structures(&self) -> Units {
+		let starts = &self.game_info.start_locations;
+		self.units.enemy.structures.filter(|s| {
+			let distance = s.distance_squared(self.start_location);
+			s.is_visible() && starts.iter().all(|start| distance < s.distance_squared(*start))
+		})
+	}
+	/// Heuristic that checks if bot is being rushed by opponent.
+	///
+	/// Returns `true` if it's earlier than [`rush_options.time`] and either there are
+	/// [`proxy_structures`](Self::proxy_structures) or supply of enemy army (workers aren't counted)
+	/// within [`rush_options.distance`] of bot's start location is at least [`rush_options.supply`].
+	///
+	/// [`rush_options.time`]: RushOptions::time
+	/// [`rush_options.distance`]: RushOptions::distance
+	/// [`rush_options.supply`]: RushOptions::supply
+	pub fn enemy_rushed(&self) -> bool {
+		let options = self.rush_options;
+		if self.time >= options.time {
+			return false;
+		}
+		let supply = self
+			.units
+			.enemy
+			.units
+			.iter()
+			.filter(|u| !u.is_worker() && u.is_closer(options.distance, self.start_location))
+			.map(|u| u.supply_cost())
+			.sum::<f32>();
+		supply >= options.supply || !self.proxy_structures().is_empty()
+	}
+	/// Heuristic that checks if opponent is doing worker rush.
+	///
+	/// Returns `true` if it's earlier than [`rush_options.time`] and there are
+	/// at least [`rush_options.workers`] enemy workers within [`rush_options.distance`]
+	/// of bot's start location.
+	///
+	/// [`rush_options.time`]: RushOptions::time
+	/// [`rush_options.workers`]: RushOptions::workers
+	/// [`rush_options.distance`]: RushOptions::distance
+	pub fn worker_rush_detected(&self) -> bool {
+		let options = self.rush_options;
+		self.time < options.time
+			&& self
+				.units
+				.enemy
+				.workers
+				.iter()
+				.filter(|u| u.is_closer(options.distance, self.start_location))
+				.count() >= options.workers
+	}
+	/// Returns positions of enemy structures inside bot's main base
+	/// (i.e. within [`rush_options.main_radius`] of bot's start location).
+	///
+	/// [`rush_options.main_radius`]: RushOptions::main_radius
+	pub fn cannon_rush_positions(&self) -> Vec<Point2> {
+		let radius = self.rush_options.main_radius;
+		self.units
+			.enemy
+			.structures
+			.iter()
+			.filter(|s| s.is_closer(radius, self.start_location))
+			.map(|s| s.position())
+			.collect()
+	}
+	/// Checks if opponent is doing cannon rush or other proxy inside bot's main base,
+	/// i.e. there're enemy structures (Pylons, Photon Cannons, ...) in it.
+	///
+	/// Use [`cannon_rush_positions`](Self::cannon_rush_positions) to get positions of these structures.
+	pub fn cannon_rush_detected(&self) -> bool {
+		let radius = self.rush_options.main_radius;
+		self.units
+			.enemy
+			.structures
+			.iter()
+			.any(|s| s.is_closer(radius, self.start_location))
+	}
 	/// Constructs new [`CountOptions`], used to count units fast and easy.
 	///
 	/// # Examples
@@ -523,19 +1460,54 @@ impl Bot {
 		CountOptions::new(self, true)
 	}
 	pub(crate) fn get_actions(&mut self) -> &[Action] {
+		let budget = self
+			.apm_limit
+			.map(|limit| limit.saturating_sub(self.actions_this_minute()) as usize);
+		let game_loop = self.state.observation.game_loop();
 		let actions = &mut self.actions;
+		let issued = actions.len();
 
 		let mut commander = self.commander.write_lock();
 
 		if !commander.commands.is_empty() {
-			actions.extend(
-				commander
-					.commands
-					.drain()
-					.map(|((ability, target, queue), units)| {
-						Action::UnitCommand(ability, target, units, queue)
-					}),
-			);
+			let game_data = &self.game_data;
+			let ability_casts = &mut self.ability_casts;
+			let mut commands = commander.commands.drain().collect::<Vec<_>>();
+			if let Some(budget) = budget {
+				let my_units = &self.units.my.all;
+				let is_redundant = |ability: AbilityId, target: Target, tag: u64| match my_units
+					.get(tag)
+					.and_then(|u| u.orders().first())
+				{
+					Some(order) => {
+						order.target == target
+							&& general_ability(game_data, order.ability)
+								== general_ability(game_data, ability)
+					}
+					None => false,
+				};
+				for ((ability, target, queue), units) in &mut commands {
+					if !*queue {
+						units.retain(|tag| !is_redundant(*ability, *target, *tag));
+					}
+				}
+				commands.retain(|(_, units)| !units.is_empty());
+
+				let budget = budget.saturating_sub(commander.autocast.len());
+				if commands.len() > budget {
+					commands.sort_by_key(|((_, _, queue), units)| (*queue, Reverse(units.len())));
+					commands.truncate(budget);
+				}
+			}
+			actions.extend(commands.into_iter().map(|((ability, target, queue), units)| {
+				let general = general_ability(game_data, ability);
+				if ABILITY_COOLDOWNS.contains_key(&general) {
+					for tag in &units {
+						ability_casts.insert((*tag, general), game_loop);
+					}
+				}
+				Action::UnitCommand(ability, target, units, queue)
+			}));
 		}
 		if !commander.autocast.is_empty() {
 			actions.extend(
@@ -546,23 +1518,46 @@ impl Bot {
 			);
 		}
 
+		let count = (actions.len() - issued) as u32;
+		if count > 0 {
+			self.action_log.push_back((game_loop, count));
+		}
 		actions
 	}
+	/// Limits number of actions bot sends per minute, `None` removes the limit (default).
+	///
+	/// While limit is set, commands are processed before sending:
+	/// - Same ability with same target given to several units is always merged into one action
+	///   (this is done regardless of limit).
+	/// - Non-queued commands which repeat unit's current order (same ability and target) are dropped.
+	/// - If remaining commands still exceed actions left for the last minute,
+	///   queued commands are dropped first, then commands given to smaller groups of units.
+	///
+	/// Autocast toggles are never dropped, but count towards the limit.
+	pub fn set_apm_limit(&mut self, actions_per_minute: Option<u32>) {
+		self.apm_limit = actions_per_minute;
+	}
+	/// Returns number of actions sent in the last minute of game time.
+	pub fn actions_this_minute(&self) -> u32 {
+		let game_loop = self.state.observation.game_loop();
+		self.action_log
+			.iter()
+			.filter(|(logged, _)| game_loop < logged + FRAMES_PER_MINUTE)
+			.map(|(_, count)| count)
+			.sum()
+	}
 	pub(crate) fn clear_actions(&mut self) {
 		self.actions.clear();
 	}
 	pub(crate) fn get_debug_commands(&mut self) -> &[DebugCommand] {
-		self.debug.get_commands()
+		self.debug.get_commands(&self.game_info.terrain_height)
 	}
 	pub(crate) fn clear_debug_commands(&mut self) {
 		self.debug.clear_commands();
 	}
 	/// Returns full cost of building given unit type, without any corrections.
 	pub fn get_unit_api_cost(&self, unit: UnitTypeId) -> Cost {
-		self.game_data
-			.units
-			.get(&unit)
-			.map_or_else(Cost::default, |data| data.cost())
+		self.game_data.cost(unit)
 	}
 	/// Returns correct cost of building given unit type.
 	pub fn get_unit_cost(&self, unit: UnitTypeId) -> Cost {
@@ -612,6 +1607,13 @@ impl Bot {
 		}
 		cost
 	}
+	/// Returns time in game seconds required to produce given unit type.
+	///
+	/// Currently it's just base build time, producer isn't taken into account
+	/// (i.e. reactors, chrono boost, larva availability are ignored).
+	pub fn time_to_produce(&self, unit: UnitTypeId) -> f32 {
+		self.get_unit_api_cost(unit).time / FRAMES_PER_SECOND
+	}
 	/// Checks if bot has enough resources and supply to build given unit type.
 	pub fn can_afford(&self, unit: UnitTypeId, check_supply: bool) -> bool {
 		let cost = self.get_unit_cost(unit);
@@ -623,12 +1625,53 @@ impl Bot {
 		}
 		true
 	}
+	/// Checks if given building (or larva, or unit that morphs) can train given unit type right now.
+	///
+	/// Checks that:
+	/// - building is a producer of this unit type (see [`ALL_PRODUCERS`]) and it's ready
+	/// - building has free queue slot (reactor is taken into account),
+	///   for Warp Gates it's checked that warp in ability is available
+	/// - tech requirement is met (see [`TECH_REQUIREMENTS`], aliases like Lair for Hatchery count too)
+	/// - add-on requirement is met (e.g. Marauder needs Tech Lab)
+	/// - bot [can afford](Self::can_afford) the unit
+	pub fn can_train(&self, building: &Unit, unit: UnitTypeId) -> bool {
+		let producer = building.type_id();
+		match ALL_PRODUCERS.get(&unit) {
+			Some(producers) if producers.contains(&producer) => {}
+			_ => return false,
+		}
+		if !building.is_ready() {
+			return false;
+		}
+
+		let has_slot = if producer == UnitTypeId::WarpGate {
+			match WARPGATE_ABILITIES.get(&unit) {
+				Some(ability) => building.has_ability(*ability),
+				None => false,
+			}
+		} else {
+			building.is_unused()
+		};
+		if !has_slot {
+			return false;
+		}
+
+		if let Some(requirement) = TECH_REQUIREMENTS.get(&unit) {
+			if self.counter().tech().count(*requirement) == 0 {
+				return false;
+			}
+		}
+		if let Some(data) = self.game_data.units.get(&unit) {
+			if data.require_attached && !building.has_techlab() {
+				return false;
+			}
+		}
+
+		self.can_afford(unit, true)
+	}
 	/// Checks cost of making given upgrade.
 	pub fn get_upgrade_cost(&self, upgrade: UpgradeId) -> Cost {
-		self.game_data
-			.upgrades
-			.get(&upgrade)
-			.map_or_else(Default::default, |data| data.cost())
+		self.game_data.upgrade_cost(upgrade)
 	}
 	/// Checks if bot has enough resources to make given upgrade.
 	pub fn can_afford_upgrade(&self, upgrade: UpgradeId) -> bool {
@@ -686,46 +1729,602 @@ impl Bot {
 			.copied()
 			.map_or(false, |count| count > 0)
 	}
-	/// Returns progress of making given upgrade.
-	/// - `1` - complete
-	/// - `0` - not even ordered
-	/// - `0..1` - in progress
-	pub fn upgrade_progress(&self, upgrade: UpgradeId) -> f32 {
-		if self.has_upgrade(upgrade) {
-			return 1.0;
+	/// Returns progress of making given upgrade.
+	/// - `1` - complete
+	/// - `0` - not even ordered
+	/// - `0..1` - in progress
+	pub fn upgrade_progress(&self, upgrade: UpgradeId) -> f32 {
+		if self.has_upgrade(upgrade) {
+			return 1.0;
+		}
+		if !self.is_ordered_upgrade(upgrade) {
+			return 0.0;
+		}
+
+		let ability = self.game_data.upgrades[&upgrade].ability;
+		self.units
+			.my
+			.structures
+			.iter()
+			.filter(|s| s.is_ready())
+			.find_map(|s| {
+				s.orders()
+					.iter()
+					.find(|order| order.ability == ability)
+					.map(|order| order.progress)
+			})
+			.unwrap_or(0.0)
+	}
+	/// Orders all units with given tags to use ability on target.
+	///
+	/// Identical commands (same ability, target and queue) given to different units,
+	/// either with this method or with [`Unit::command`], are grouped into single action,
+	/// which reduces number of actions sent to game and bot's APM.
+	pub fn command_tags(&mut self, tags: &[u64], ability: AbilityId, target: Target, queue: bool) {
+		if tags.is_empty() {
+			return;
+		}
+		self.commander
+			.write_lock()
+			.commands
+			.entry((ability, target, queue))
+			.or_default()
+			.extend_from_slice(tags);
+	}
+	/// Orders transport (Medivac, Warp Prism, Overlord, Bunker, Nydus, Command Center)
+	/// to load given passenger.
+	///
+	/// Returns `false` without giving orders if there's not enough free space in transport
+	/// (passenger's [`cargo_size`](Unit::cargo_size) is bigger than [`cargo_left`](Unit::cargo_left)).
+	pub fn load(&mut self, transport: &Unit, passenger: &Unit) -> bool {
+		if passenger.cargo_size() > transport.cargo_left().unwrap_or(0) {
+			return false;
+		}
+		let target = Target::Tag(passenger.tag());
+		match transport.type_id() {
+			UnitTypeId::Medivac => transport.command(AbilityId::LoadMedivac, target, false),
+			UnitTypeId::WarpPrism | UnitTypeId::WarpPrismPhasing => {
+				transport.command(AbilityId::LoadWarpPrism, target, false)
+			}
+			UnitTypeId::OverlordTransport => transport.command(AbilityId::LoadOverlord, target, false),
+			UnitTypeId::Bunker => transport.command(AbilityId::LoadBunker, target, false),
+			UnitTypeId::NydusNetwork => transport.command(AbilityId::LoadNydusNetwork, target, false),
+			UnitTypeId::NydusCanal => transport.command(AbilityId::LoadNydusWorm, target, false),
+			// Command Center can only load all nearby SCVs, so passenger is ordered to get in instead
+			UnitTypeId::CommandCenter => passenger.smart(Target::Tag(transport.tag()), false),
+			_ => transport.command(AbilityId::Load, target, false),
+		}
+		true
+	}
+	/// Orders transport to unload all passengers.
+	///
+	/// Flying transports unload at given position,
+	/// while Bunker, Nydus and Command Center unload in place and `at` is ignored.
+	pub fn unload_all(&mut self, transport: &Unit, at: Point2) {
+		let target = Target::Pos(at);
+		match transport.type_id() {
+			UnitTypeId::Medivac => transport.command(AbilityId::UnloadAllAtMedivac, target, false),
+			UnitTypeId::WarpPrism | UnitTypeId::WarpPrismPhasing => {
+				transport.command(AbilityId::UnloadAllAtWarpPrism, target, false)
+			}
+			UnitTypeId::OverlordTransport => transport.command(AbilityId::UnloadAllAtOverlord, target, false),
+			UnitTypeId::Bunker => transport.use_ability(AbilityId::UnloadAllBunker, false),
+			UnitTypeId::NydusNetwork => transport.use_ability(AbilityId::UnloadAllNydasNetwork, false),
+			UnitTypeId::NydusCanal => transport.use_ability(AbilityId::UnloadAllNydusWorm, false),
+			UnitTypeId::CommandCenter => transport.use_ability(AbilityId::UnloadAllCommandCenter, false),
+			_ => transport.command(AbilityId::UnloadAllAt, target, false),
+		}
+	}
+	/// Heuristic that checks if bot is going to be supply blocked in given number of frames.
+	///
+	/// Each ready production structure (townhalls, Barracks, Gateways, ...) or larva
+	/// is assumed to use `1` supply per `12` seconds (build time of worker), while each supply provider
+	/// in progress is counted as `8` supply.
+	pub fn supply_block_predicted(&self, lead_frames: f32) -> bool {
+		if self.supply_cap >= 200 {
+			return false;
+		}
+		let producers = self
+			.units
+			.my
+			.structures
+			.iter()
+			.filter(|s| {
+				s.is_ready()
+					&& (s.is_townhall()
+						|| matches!(
+							s.type_id(),
+							UnitTypeId::Barracks
+								| UnitTypeId::Factory | UnitTypeId::Starport
+								| UnitTypeId::Gateway | UnitTypeId::WarpGate
+								| UnitTypeId::RoboticsFacility
+								| UnitTypeId::Stargate
+						))
+			})
+			.count() + self.units.my.larvas.len();
+		let pending = self.counter().ordered().count(self.race_values.supply) * 8;
+		let usage = producers as f32 * (lead_frames / (12.0 * FRAMES_PER_SECOND)).max(1.0);
+		((self.supply_left as usize + pending) as f32) < usage
+	}
+	/// Builds supply provider of bot's race if [supply block is predicted](Self::supply_block_predicted)
+	/// in given number of frames and there's no other supply provider in progress.
+	///
+	/// Zerg trains Overlord from larva. Terran and Protoss take [available builder](Self::available_builder)
+	/// and build Supply Depot or Pylon near townhall closest to it.
+	/// Cost of supply provider is [subtracted](Self::subtract_resources) from bot's resources.
+	///
+	/// Returns `true` if supply provider was ordered.
+	pub fn build_supply_if_needed(&mut self, lead_frames: f32) -> bool {
+		let supply = self.race_values.supply;
+		if !self.supply_block_predicted(lead_frames)
+			|| self.counter().ordered().count(supply) > 0
+			|| !self.can_afford(supply, false)
+		{
+			return false;
+		}
+
+		if self.race == Race::Zerg {
+			match self.units.my.larvas.first() {
+				Some(larva) => larva.train(supply, false),
+				None => return false,
+			}
+			self.subtract_resources(supply, false);
+			return true;
+		}
+
+		let worker = match self.available_builder(self.start_location) {
+			Some(worker) => worker.clone(),
+			None => return false,
+		};
+		let near = self
+			.units
+			.my
+			.townhalls
+			.closest(&worker)
+			.map_or(worker.position(), |townhall| townhall.position());
+		let pos = match self.find_placement(supply, near, Default::default()) {
+			Some(pos) => pos,
+			None => return false,
+		};
+		worker.build(supply, pos, false);
+		self.subtract_resources(supply, false);
+		true
+	}
+	/// Orders nexus to chrono boost target structure.
+	///
+	/// Returns `false` without giving orders if nexus doesn't have enough energy (`50`)
+	/// or target is already chrono boosted.
+	pub fn chrono_boost(&mut self, nexus: &Unit, target: &Unit) -> bool {
+		if nexus.energy().unwrap_or(0) < 50
+			|| target.has_any_buff(&[BuffId::ChronoBoostEnergyCost, BuffId::TimeWarpProduction])
+		{
+			return false;
+		}
+		nexus.command(
+			AbilityId::EffectChronoBoostEnergyCost,
+			Target::Tag(target.tag()),
+			false,
+		);
+		true
+	}
+	/// Spends energy of all ready nexuses on chrono boosting producing structures.
+	///
+	/// Structures are chosen by type in order of given `priorities`,
+	/// only busy ones that aren't already chrono boosted are taken.
+	///
+	/// Returns `true` if at least one chrono boost was applied.
+	pub fn auto_chrono(&mut self, priorities: &[UnitTypeId]) -> bool {
+		let mut nexuses = self
+			.units
+			.my
+			.townhalls
+			.iter()
+			.filter(|t| t.type_id() == UnitTypeId::Nexus && t.is_ready() && t.energy().unwrap_or(0) >= 50)
+			.cloned()
+			.collect::<Vec<_>>();
+		if nexuses.is_empty() {
+			return false;
+		}
+
+		let structures = &self.units.my.structures;
+		let targets = priorities
+			.iter()
+			.flat_map(|id| {
+				structures.iter().filter(move |s| {
+					s.type_id() == *id
+						&& s.is_ready() && !s.is_idle()
+						&& !s.has_any_buff(&[BuffId::ChronoBoostEnergyCost, BuffId::TimeWarpProduction])
+				})
+			})
+			.cloned()
+			.collect::<Vec<_>>();
+
+		let mut applied = false;
+		for target in targets {
+			match nexuses.pop() {
+				Some(nexus) => applied |= self.chrono_boost(&nexus, &target),
+				None => break,
+			}
+		}
+		applied
+	}
+	/// Sends message to in-game chat.
+	///
+	/// Can be called multiple times per step, all messages will be sent in the same action request.
+	///
+	/// Note: Game limits length of chat messages, so too long messages will be truncated.
+	/// Also sending a lot of messages in a short period of time may get them dropped by spam protection.
+	pub fn chat(&mut self, message: &str) {
+		self.actions.push(Action::Chat(message.to_string(), false));
+	}
+	/// Sends message for allies only to in-game chat (can be used for debug).
+	///
+	/// Has the same limits as [`chat`](Self::chat).
+	pub fn chat_ally(&mut self, message: &str) {
+		self.actions.push(Action::Chat(message.to_string(), true));
+	}
+	/// Sends message to team channel of in-game chat, alias for [`chat_ally`](Self::chat_ally).
+	#[inline]
+	pub fn chat_team(&mut self, message: &str) {
+		self.chat_ally(message);
+	}
+	/// Returns units from given collection, which are in `range` from given unit.
+	///
+	/// Range is measured between edges of units (i.e. radiuses are taken into account).
+	pub fn units_in_range(&self, of: &Unit, range: f32, from: &Units) -> Units {
+		from.filter(|u| of.is_closer(range + of.radius() + u.radius(), *u))
+	}
+	/// Returns enemy units in weapon range of given unit plus `extra` distance
+	/// (range upgrades are taken into account), i.e. enemies that unit can attack.
+	///
+	/// Enemies are looked up in the [spatial index](Self::spatial_grid),
+	/// so it's cheap enough to call for every unit on every step.
+	pub fn enemies_in_range(&self, of: &Unit, extra: f32) -> Units {
+		let grid = self.spatial_grid();
+		let reach = of.real_ground_range().max(of.real_air_range()) + extra + of.radius() + grid.max_radius();
+		let pos = of.position();
+		let enemies = &self.units.enemy.all;
+		grid.candidates(pos - reach, pos + reach)
+			.filter_map(|tag| enemies.get(tag))
+			.filter(|u| of.in_real_range(u, extra))
+			.cloned()
+			.collect()
+	}
+	/// Returns tag of enemy unit, which most recently damaged given bot's unit.
+	///
+	/// Attacker isn't reported by the game, so it's guessed every time unit loses health or shield:
+	/// it's the closest enemy in range, which can attack the unit, or just the closest one
+	/// that can attack it if none are in range. Guess may be wrong when attacker is hidden
+	/// (cloaked, in fog, in bunker) or damage came from spells and effects (Storm, Bile, mines, ...).
+	///
+	/// Tag stays until unit is damaged again, so attacker may be already dead or gone.
+	pub fn attacker_of(&self, unit: &Unit) -> Option<u64> {
+		self.attackers.get(&unit.tag()).copied()
+	}
+	/// Returns spatial index over [`units.all`](AllUnits::all).
+	///
+	/// Index is built on the first call in a step and reused until the next step.
+	pub fn spatial_grid(&self) -> &SpatialGrid {
+		self.spatial
+			.get_or_create(|| SpatialGrid::new(&self.units.all, DEFAULT_CELL_SIZE))
+	}
+	/// Returns all units (own, enemy and neutral) with centers in `radius` from `center`.
+	///
+	/// Uses [spatial index](Self::spatial_grid), so it's much faster than filtering all units.
+	pub fn query_radius(&self, center: Point2, radius: f32) -> Units {
+		self.spatial_grid().query_radius(&self.units.all, center, radius)
+	}
+	/// Returns all units (own, enemy and neutral) with centers inside of rectangle with given corners.
+	///
+	/// Uses [spatial index](Self::spatial_grid), so it's much faster than filtering all units.
+	pub fn query_rect(&self, p0: Point2, p1: Point2) -> Units {
+		self.spatial_grid().query_rect(&self.units.all, p0, p1)
+	}
+	/// Checks if given ability of unit isn't on cooldown.
+	///
+	/// Cooldowns are tracked by the bot: time of every cast issued through unit commands is saved
+	/// and compared with duration from [`ABILITY_COOLDOWNS`]. Abilities not present there are
+	/// always considered ready, so energy and other requirements should be checked separately.
+	///
+	/// Note: Casts can fail (e.g. target out of range), in such case ability is still considered
+	/// to be on cooldown until it's duration passes.
+	pub fn ability_ready(&self, unit: &Unit, ability: AbilityId) -> bool {
+		let general = general_ability(&self.game_data, ability);
+		let cooldown = match ABILITY_COOLDOWNS.get(&general) {
+			Some(cooldown) => *cooldown,
+			None => return true,
+		};
+		match self.ability_casts.get(&(unit.tag(), general)) {
+			Some(cast) => self.state.observation.game_loop() >= cast + cooldown,
+			None => true,
+		}
+	}
+	/// Fast local approximation of checking if unit can use given ability right now,
+	/// which doesn't send any requests to the server.
+	///
+	/// Checks that unit is bot's own ready unit (not hallucination), has enough energy
+	/// (see [`ABILITY_ENERGY_COSTS`]), has required upgrade (see [`ABILITY_UPGRADES`])
+	/// and ability [isn't on cooldown](Self::ability_ready).
+	///
+	/// Note: It's a best-effort check, abilities missing from tables are considered available.
+	/// Authoritative check is [`Unit::has_ability`], which uses abilities queried from the server.
+	pub fn ability_available(&self, unit: &Unit, ability: AbilityId) -> bool {
+		if !unit.is_mine() || !unit.is_ready() || unit.is_hallucination() {
+			return false;
+		}
+		let general = general_ability(&self.game_data, ability);
+		if let Some(cost) = ABILITY_ENERGY_COSTS
+			.get(&ability)
+			.or_else(|| ABILITY_ENERGY_COSTS.get(&general))
+		{
+			if unit.energy().unwrap_or(0) < *cost {
+				return false;
+			}
+		}
+		if let Some(upgrade) = ABILITY_UPGRADES
+			.get(&ability)
+			.or_else(|| ABILITY_UPGRADES.get(&general))
+		{
+			if !self.has_upgrade(*upgrade) {
+				return false;
+			}
+		}
+		self.ability_ready(unit, ability)
+	}
+	/// Returns closest to given position caster from the group,
+	/// which has at least `min_energy` and can use given ability right now.
+	///
+	/// Ability is checked with [`Unit::has_ability`] and [`ability_ready`](Self::ability_ready),
+	/// useful to choose which Queen transfuses, which Medivac heals or which Raven casts.
+	pub fn closest_unit_with_energy<'a>(
+		&self,
+		units: &'a Units,
+		ability: AbilityId,
+		min_energy: u32,
+		to: Point2,
+	) -> Option<&'a Unit> {
+		units
+			.iter()
+			.filter(|u| {
+				u.energy().unwrap_or(0) >= min_energy
+					&& u.has_ability(ability)
+					&& self.ability_ready(u, ability)
+			})
+			.closest(to)
+	}
+	/// Returns radius of given effect around every it's position.
+	///
+	/// Taken from [`EFFECT_RADIUS`], falls back to game data for effects missing there.
+	pub fn effect_radius(&self, effect: EffectId) -> f32 {
+		match EFFECT_RADIUS.get(&effect) {
+			Some(radius) => *radius,
+			None => self
+				.game_data
+				.effects
+				.get(&effect)
+				.map_or(0.0, |data| data.radius),
+		}
+	}
+	/// Checks if given effect can damage bot's units.
+	///
+	/// Damaging effects (see [`DAMAGING_EFFECTS`]) are dangerous when casted by opponent,
+	/// own and allied ones only when they have friendly fire (e.g. Psi Storm, Nuke, Corrosive Bile).
+	pub fn is_dangerous_effect(&self, effect: &Effect) -> bool {
+		match DAMAGING_EFFECTS.get(&effect.id) {
+			Some(friendly_fire) => *friendly_fire || effect.alliance.is_enemy(),
+			None => false,
+		}
+	}
+	/// Returns visible enemy detectors (Observers, Overseers, Ravens, Missile Turrets,
+	/// Spore Crawlers and powered Photon Cannons).
+	///
+	/// Active enemy scans aren't units, they're taken into account
+	/// in [`is_position_detected`](Self::is_position_detected).
+	pub fn enemy_detectors(&self) -> Units {
+		self.units.enemy.all.filter(|u| u.is_detector())
+	}
+	/// Checks if given position is in detection range of any enemy detector or scanner sweep,
+	/// i.e. if cloaked and burrowed units are revealed there.
+	pub fn is_position_detected(&self, pos: Point2) -> bool {
+		self.units.enemy.all.iter().any(|d| {
+			let range = if d.detect_range() > 0.0 {
+				d.detect_range()
+			} else {
+				11.0
+			};
+			d.is_detector() && pos.is_closer(d.radius() + range, d)
+		}) || self
+			.state
+			.observation
+			.raw
+			.effects
+			.iter()
+			.filter(|e| e.id == EffectId::ScannerSweep && e.alliance.is_enemy())
+			.any(|e| {
+				let radius = self.effect_radius(e.id).max(e.radius);
+				e.positions.iter().any(|p| pos.is_closer(radius, *p))
+			})
+	}
+	/// Returns effects on the map which can damage bot's units.
+	pub fn dangerous_effects(&self) -> Vec<&Effect> {
+		self.state
+			.observation
+			.raw
+			.effects
+			.iter()
+			.filter(|e| self.is_dangerous_effect(e))
+			.collect()
+	}
+	/// Moves unit out of damaging effects (Psi Storm, Corrosive Bile, Lurker spines, Liberator zone, ...)
+	/// and range of enemy burrowed Widow Mines if it stands in or near them.
+	///
+	/// Unit is sent to the closest safe position (pathable for ground units) within 10 distance.
+	/// If unit is already moving to safe position, it's not given another order.
+	///
+	/// Returns `true` if unit is dodging.
+	pub fn dodge(&mut self, unit: &Unit) -> bool {
+		let flying = unit.is_flying();
+		let margin = unit.radius() + 0.5;
+		let mut zones = self
+			.dangerous_effects()
+			.into_iter()
+			.filter(|e| {
+				!(flying
+					&& matches!(
+						e.id,
+						EffectId::LiberatorTargetMorphDelayPersistent
+							| EffectId::LiberatorTargetMorphPersistent
+					))
+			})
+			.flat_map(|e| {
+				let radius = self.effect_radius(e.id).max(e.radius) + margin;
+				e.positions.iter().map(move |pos| (*pos, radius))
+			})
+			.collect::<Vec<_>>();
+		zones.extend(
+			self.units
+				.enemy
+				.all
+				.iter()
+				.filter(|u| u.type_id() == UnitTypeId::WidowMineBurrowed)
+				.map(|u| (u.position(), 5.0 + margin)),
+		);
+		let in_danger = |pos: Point2| {
+			zones
+				.iter()
+				.any(|(center, radius)| pos.is_closer(*radius, *center))
+		};
+
+		let pos = unit.position();
+		if !in_danger(pos) {
+			return false;
+		}
+		if let Some(order) = unit.orders().first() {
+			if let Target::Pos(target) = order.target {
+				if matches!(order.ability, AbilityId::MoveMove | AbilityId::Move) && !in_danger(target) {
+					return true;
+				}
+			}
+		}
+
+		for distance in 1..=10 {
+			let distance = distance as f32;
+			let safe = (0..16)
+				.map(|i| {
+					let angle = i as f32 * std::f32::consts::PI / 8.0;
+					pos.offset(distance * angle.cos(), distance * angle.sin())
+				})
+				.find(|p| (flying || self.is_pathable(*p)) && !in_danger(*p));
+			if let Some(safe) = safe {
+				unit.move_to(Target::Pos(safe), false);
+				return true;
+			}
+		}
+		false
+	}
+	/// Leaves the game after the current step (actions of this step are still sent).
+	///
+	/// Leaving counts as defeat, so [`on_end`] is called with [`GameResult::Defeat`]
+	/// and the game loop stops instead of calling [`on_step`] again.
+	/// Unlike [`leave`](Self::leave) request isn't sent immediately.
+	///
+	/// If runner has `save_replay_as` set, replay is saved right before leaving,
+	/// since it can't be requested once bot is out of the game.
+	///
+	/// [`on_end`]: crate::Player::on_end
+	/// [`on_step`]: crate::Player::on_step
+	/// [`GameResult::Defeat`]: crate::player::GameResult::Defeat
+	pub fn leave_game(&mut self) {
+		self.leave_requested = true;
+	}
+	/// Concedes the game: sends "gg" to chat and [leaves the game](Self::leave_game).
+	pub fn surrender(&mut self) {
+		self.chat("gg");
+		self.leave_game();
+	}
+	/// Spawns `count` units of given type for player with id `owner` on given position.
+	///
+	/// Note: Debug commands work only in games with cheats allowed (i.e. don't work on ladder).
+	#[cfg(feature = "debug")]
+	pub fn debug_create_unit(&mut self, unit: UnitTypeId, owner: u32, pos: Point2, count: u32) {
+		self.debug.create_units(&[(unit, Some(owner), pos, count)]);
+	}
+	/// Kills units with given tags.
+	///
+	/// Note: Debug commands work only in games with cheats allowed (i.e. don't work on ladder).
+	#[cfg(feature = "debug")]
+	pub fn debug_kill_units(&mut self, tags: &[u64]) {
+		self.debug.kill_units(tags);
+	}
+	/// Gives at least given amount of minerals and vespene gas to the bot.
+	///
+	/// Debug API can only give resources in portions of 5000,
+	/// so actual amount is rounded up to the closest multiple of 5000.
+	///
+	/// Note: Debug commands work only in games with cheats allowed (i.e. don't work on ladder).
+	#[cfg(feature = "debug")]
+	pub fn debug_give_resources(&mut self, minerals: u32, vespene: u32) {
+		for _ in 0..minerals.div_ceil(5000) {
+			self.debug.cheat_minerals();
 		}
-		if !self.is_ordered_upgrade(upgrade) {
-			return 0.0;
+		for _ in 0..vespene.div_ceil(5000) {
+			self.debug.cheat_gas();
 		}
-
-		let ability = self.game_data.upgrades[&upgrade].ability;
-		self.units
-			.my
-			.structures
-			.iter()
-			.filter(|s| s.is_ready())
-			.find_map(|s| {
-				s.orders()
-					.iter()
-					.find(|order| order.ability == ability)
-					.map(|order| order.progress)
-			})
-			.unwrap_or(0.0)
 	}
-	/// Sends message to in-game chat.
-	pub fn chat(&mut self, message: &str) {
-		self.actions.push(Action::Chat(message.to_string(), false));
+	/// Sets health, energy or shields of unit with given tag to given amount.
+	///
+	/// Note: Debug commands work only in games with cheats allowed (i.e. don't work on ladder).
+	#[cfg(feature = "debug")]
+	pub fn debug_set_unit_value(&mut self, tag: u64, value: UnitValue, amount: f32) {
+		self.debug.set_unit_value(tag, value, amount);
 	}
-	/// Sends message for allies only to in-game chat (can be used for debug).
-	pub fn chat_ally(&mut self, message: &str) {
-		self.actions.push(Action::Chat(message.to_string(), true));
+	/// Toggles invincibility of bot's units (also significantly increases their damage).
+	///
+	/// Note: Debug commands work only in games with cheats allowed (i.e. don't work on ladder).
+	#[cfg(feature = "debug")]
+	pub fn debug_god(&mut self) {
+		self.debug.cheat_god();
+	}
+	/// Draws colored box on each tile around bot's townhalls to visualize placement grid:
+	/// green - placeable, red - not placeable, blue - has creep.
+	///
+	/// Only tiles within `10` distance from townhalls are drawn to keep debug request small.
+	#[cfg(feature = "debug")]
+	pub fn debug_draw_placement_grid(&mut self) {
+		const RADIUS: isize = 10;
+
+		let mut tiles = FxHashSet::default();
+		for townhall in self.units.my.townhalls.iter().filter(|t| !t.is_flying()) {
+			let (x, y) = <(usize, usize)>::from(townhall.position());
+			for dx in -RADIUS..=RADIUS {
+				for dy in -RADIUS..=RADIUS {
+					let (tx, ty) = (x as isize + dx, y as isize + dy);
+					if tx >= 0 && ty >= 0 {
+						tiles.insert((tx as usize, ty as usize));
+					}
+				}
+			}
+		}
+
+		for (x, y) in tiles {
+			let color = if self.has_creep((x, y)) {
+				(0, 0, 255)
+			} else if self.is_placeable((x, y)) {
+				(0, 255, 0)
+			} else {
+				(255, 0, 0)
+			};
+			let (x, y) = (x as f32, y as f32);
+			self.debug.debug_box2_on(
+				Point2::new(x + 0.1, y + 0.1),
+				Point2::new(x + 0.9, y + 0.9),
+				Some(color),
+			);
+		}
 	}
 	/// Returns actual terrain height on given position in 3D space.
 	pub fn get_z_height<P: Into<(usize, usize)>>(&self, pos: P) -> f32 {
-		self.game_info
-			.terrain_height
-			.get(pos.into())
-			.map_or(0.0, |h| *h as f32 * 32.0 / 255.0 - 16.0)
+		z_height(&self.game_info.terrain_height, pos.into())
 	}
 	/// Returns terrain height on given position.
 	pub fn get_height<P: Into<(usize, usize)>>(&self, pos: P) -> u8 {
@@ -805,6 +2404,75 @@ impl Bot {
 			.get(pos.into())
 			.map_or(false, |p| p.is_empty())
 	}
+	/// Checks if given position is powered by psionic matrix of pylon or warp prism.
+	pub fn has_power<P: Into<Point2>>(&self, pos: P) -> bool {
+		let pos = pos.into();
+		self.state
+			.observation
+			.raw
+			.psionic_matrix
+			.iter()
+			.any(|m| pos.is_closer(m.radius, m.pos))
+	}
+	/// Returns up to `count` positions to warp in units, in power field of
+	/// ready Pylon or phasing Warp Prism closest to `near`.
+	///
+	/// Checked positions are:
+	/// - pathable on the [`pathing_grid`], which is received at the game start
+	///   and doesn't include structures;
+	/// - at least `0.5` away from radius of every ground unit or structure;
+	/// - spread at least `1.5` apart, so warped units don't overlap.
+	///
+	/// Closest to `near` positions go first.
+	/// Less than `count` positions are returned if there's not enough space.
+	/// Positions aren't validated by the game, so warp in can still fail in rare cases
+	/// (e.g. near corners of large structures, since their footprints are squares).
+	///
+	/// [`pathing_grid`]: crate::game_info::GameInfo::pathing_grid
+	pub fn warp_in_positions(&self, near: Point2, count: usize) -> Vec<Point2> {
+		let matrix = match self.state.observation.raw.psionic_matrix.iter().min_by(|a, b| {
+			a.pos
+				.distance_squared(near)
+				.partial_cmp(&b.pos.distance_squared(near))
+				.unwrap()
+		}) {
+			Some(matrix) => matrix,
+			None => return Vec::new(),
+		};
+
+		let (cx, cy) = (matrix.pos.x.floor() as isize, matrix.pos.y.floor() as isize);
+		let r = matrix.radius.ceil() as isize;
+		let mut candidates = iproduct!((cx - r)..=(cx + r), (cy - r)..=(cy + r))
+			.map(|(x, y)| Point2::new(x as f32 + 0.5, y as f32 + 0.5))
+			.filter(|pos| pos.is_closer(matrix.radius, matrix.pos) && self.is_pathable(*pos))
+			.collect::<Vec<_>>();
+		candidates.sort_by(|a, b| {
+			a.distance_squared(near)
+				.partial_cmp(&b.distance_squared(near))
+				.unwrap()
+		});
+
+		let grid = self.spatial_grid();
+		let reach = grid.max_radius() + 0.5;
+		let mut positions: Vec<Point2> = Vec::with_capacity(count);
+		for pos in candidates {
+			if positions.len() >= count {
+				break;
+			}
+			if positions.iter().any(|p| p.is_closer(1.5, pos)) {
+				continue;
+			}
+			if grid
+				.query_radius(&self.units.all, pos, reach)
+				.iter()
+				.any(|u| !u.is_flying() && u.is_closer(u.radius() + 0.5, pos))
+			{
+				continue;
+			}
+			positions.push(pos);
+		}
+		positions
+	}
 	pub(crate) fn init_data_for_unit(&mut self) {
 		self.race = self.game_info.players[&self.player_id].race_actual.unwrap();
 		if self.game_info.players.len() == 2 {
@@ -822,6 +2490,7 @@ impl Bot {
 			race_values: Rs::clone(&self.race_values),
 			max_cooldowns: Rs::clone(&self.max_cooldowns),
 			last_units_health: Rs::clone(&self.last_units_health),
+			last_units_position: Rs::clone(&self.last_units_position),
 			abilities_units: Rs::clone(&self.abilities_units),
 			enemy_upgrades: Rs::clone(&self.enemy_upgrades),
 			upgrades: Rs::clone(&self.state.observation.raw.upgrades),
@@ -831,13 +2500,38 @@ impl Bot {
 			available_frames: Rs::clone(&self.available_frames),
 		});
 	}
+	/// Clears state left from previous game, so the same bot can play several games in a row.
+	pub(crate) fn reset_for_new_game(&mut self) {
+		self.units = Default::default();
+		self.owned_tags.clear();
+		self.under_construction.clear();
+		self.saved_hallucinations.clear();
+		self.enemy_upgrades.write_lock().clear();
+		self.techlab_tags.write_lock().clear();
+		self.reactor_tags.write_lock().clear();
+		self.max_cooldowns.write_lock().clear();
+		self.last_units_health.write_lock().clear();
+		self.last_units_position.write_lock().clear();
+		self.last_units_loop = 0;
+		self.available_frames.write_lock().clear();
+		self.abilities_units.write_lock().clear();
+		self.attackers.clear();
+		self.profile.clear();
+		self.reserved_tiles.clear();
+		self.ability_casts.clear();
+		self.action_log.clear();
+		self.leave_requested = false;
+	}
 	pub(crate) fn prepare_start(&mut self) {
+		self.path_distances.clear();
 		if let Some(townhall) = self.units.my.townhalls.first() {
 			self.start_location = townhall.position();
 		}
 		if let Some(pos) = self.game_info.start_locations.first() {
 			self.enemy_start = *pos;
 		}
+		self.possible_enemy_starts = self.game_info.start_locations.clone();
+		self.known_enemy_start = None;
 
 		let resources = self.units.resources.closer(11.0, self.start_location);
 		self.start_center =
@@ -1055,12 +2749,40 @@ impl Bot {
 		if let Some(ramp) = get_closest_ramp(self.enemy_start) {
 			self.ramps.enemy = ramp;
 		}
+		if let Some(natural) = self.expansions.get(1) {
+			let loc = natural.loc;
+			let main_ramp = &self.ramps.my.points;
+			let dist = |r: &&Ramp| {
+				r.points
+					.iter()
+					.map(|p| Point2::from(*p))
+					.center()
+					.map_or(f32::INFINITY, |c| c.distance_squared(loc))
+			};
+			if let Some(ramp) = ramps
+				.iter()
+				.filter(|r| &r.points != main_ramp)
+				.min_by(|a, b| dist(a).partial_cmp(&dist(b)).unwrap())
+			{
+				self.ramps.natural = ramp.clone();
+			}
+		}
 
 		self.ramps.all = ramps;
 	}
 	pub(crate) fn prepare_step(&mut self) {
 		let observation = &self.state.observation;
 		self.time = (observation.game_loop() as f32) / FRAMES_PER_SECOND;
+		let game_loop = observation.game_loop();
+		self.ability_casts
+			.retain(|(_, ability), cast| game_loop < *cast + ABILITY_COOLDOWNS[ability]);
+		self.reserved_tiles.clear();
+		while let Some((logged, _)) = self.action_log.front() {
+			if game_loop < logged + FRAMES_PER_MINUTE {
+				break;
+			}
+			self.action_log.pop_front();
+		}
 		let common = &observation.common;
 		self.minerals = common.minerals;
 		self.vespene = common.vespene;
@@ -1105,6 +2827,47 @@ impl Bot {
 		}
 		self.current_units = current_units;
 		self.orders = orders;
+
+		// Resolving enemy start location
+		if self.known_enemy_start.is_none() {
+			let townhalls = &self.units.enemy.townhalls;
+			if let Some(start) = self.possible_enemy_starts.iter().copied().find(|start| {
+				townhalls
+					.iter()
+					.any(|t| t.is_visible() && t.is_closer(5.0, *start))
+			}) {
+				self.known_enemy_start = Some(start);
+				self.possible_enemy_starts = vec![start];
+			} else {
+				let mut starts = std::mem::take(&mut self.possible_enemy_starts);
+				starts.retain(|start| !self.is_visible(*start));
+				self.possible_enemy_starts = starts;
+			}
+		}
+
+		// Spatial index will be rebuilt on first query
+		self.spatial = LazyInit::new();
+
+		// Guessing attackers of damaged units
+		let my_units = &self.units.my.all;
+		self.attackers.retain(|tag, _| my_units.contains_tag(*tag));
+		let attackers = my_units
+			.iter()
+			.filter(|u| u.is_attacked())
+			.filter_map(|u| Some((u.tag(), self.guess_attacker(u)?)))
+			.collect::<Vec<_>>();
+		self.attackers.extend(attackers);
+	}
+	fn guess_attacker(&self, unit: &Unit) -> Option<u64> {
+		let pos = unit.position();
+		let enemies = &self.units.enemy.all;
+		self.spatial_grid()
+			.candidates(pos - 15.0, pos + 15.0)
+			.filter_map(|tag| enemies.get(tag))
+			.filter(|e| e.can_attack_unit(unit))
+			.map(|e| (!e.in_real_range(unit, 1.0), e.distance_squared(unit), e.tag()))
+			.min_by(|a, b| a.partial_cmp(b).unwrap())
+			.map(|(_, _, tag)| tag)
 	}
 	pub(crate) fn update_units(&mut self, all_units: Units) {
 		*self.last_units_health.write_lock() = self
@@ -1113,6 +2876,15 @@ impl Bot {
 			.iter()
 			.filter_map(|u| Some((u.tag(), u.hits()?)))
 			.collect();
+		let last_loop = self.last_units_loop;
+		*self.last_units_position.write_lock() = self
+			.units
+			.all
+			.iter()
+			.filter(|u| u.is_visible())
+			.map(|u| (u.tag(), (u.position(), last_loop)))
+			.collect();
+		self.last_units_loop = self.state.observation.game_loop();
 
 		self.units.clear();
 
@@ -1140,30 +2912,11 @@ impl Bot {
 				Alliance::Neutral => match u.type_id() {
 					UnitTypeId::XelNagaTower => add_to!(units.watchtowers),
 
-					UnitTypeId::RichMineralField
-					| UnitTypeId::RichMineralField750
-					| UnitTypeId::MineralField
-					| UnitTypeId::MineralField450
-					| UnitTypeId::MineralField750
-					| UnitTypeId::LabMineralField
-					| UnitTypeId::LabMineralField750
-					| UnitTypeId::PurifierRichMineralField
-					| UnitTypeId::PurifierRichMineralField750
-					| UnitTypeId::PurifierMineralField
-					| UnitTypeId::PurifierMineralField750
-					| UnitTypeId::BattleStationMineralField
-					| UnitTypeId::BattleStationMineralField750
-					| UnitTypeId::MineralFieldOpaque
-					| UnitTypeId::MineralFieldOpaque900 => {
+					id if MINERAL_FIELD_IDS.contains(&id) => {
 						add_to!(units.resources);
 						add_to!(units.mineral_fields);
 					}
-					UnitTypeId::VespeneGeyser
-					| UnitTypeId::SpacePlatformGeyser
-					| UnitTypeId::RichVespeneGeyser
-					| UnitTypeId::ProtossVespeneGeyser
-					| UnitTypeId::PurifierVespeneGeyser
-					| UnitTypeId::ShakurasVespeneGeyser => {
+					id if VESPENE_GEYSER_IDS.contains(&id) => {
 						add_to!(units.resources);
 						add_to!(units.vespene_geysers);
 					}
@@ -1507,6 +3260,138 @@ impl Bot {
 		)
 		.unwrap()[0] == ActionResult::Success
 	}
+	/// Fast local version of [`can_place`](Self::can_place), which doesn't send any requests to the server.
+	///
+	/// Checks that all tiles of building's footprint are placeable,
+	/// zerg structures are on creep (except Hatchery and Extractor)
+	/// and protoss structures are powered (except Pylon, Nexus and Assimilator).
+	/// Gas buildings are checked to be placed on free vespene geyser.
+	///
+	/// Tiles reserved with [`reserve_building`](Self::reserve_building) are treated as occupied.
+	///
+	/// Note: It doesn't take into account units and structures which block placement,
+	/// so server query ([`can_place`](Self::can_place)) still should be used as authoritative check.
+	pub fn can_place_locally(&self, building: UnitTypeId, pos: Point2) -> bool {
+		let data = match self.game_data.units.get(&building) {
+			Some(data) => data,
+			None => return false,
+		};
+
+		if matches!(
+			building,
+			UnitTypeId::Refinery
+				| UnitTypeId::RefineryRich
+				| UnitTypeId::Assimilator
+				| UnitTypeId::AssimilatorRich
+				| UnitTypeId::Extractor
+				| UnitTypeId::ExtractorRich
+		) {
+			return self.units.vespene_geysers.iter().any(|g| g.is_closer(0.5, pos))
+				&& !self
+					.units
+					.my
+					.gas_buildings
+					.iter()
+					.chain(self.units.enemy.gas_buildings.iter())
+					.any(|u| u.is_closer(0.5, pos));
+		}
+
+		let radius = match data
+			.ability
+			.and_then(|a| self.game_data.abilities.get(&a))
+			.and_then(|a| a.footprint_radius)
+		{
+			Some(radius) => radius,
+			None => return false,
+		};
+		let size = (radius * 2.0) as isize;
+		let x0 = (pos.x - radius + 0.5).floor() as isize;
+		let y0 = (pos.y - radius + 0.5).floor() as isize;
+		let needs_creep =
+			data.race.is_zerg() && !matches!(building, UnitTypeId::Hatchery | UnitTypeId::NydusCanal);
+
+		for x in x0..x0 + size {
+			for y in y0..y0 + size {
+				if x < 0 || y < 0 {
+					return false;
+				}
+				let tile = (x as usize, y as usize);
+				if !self.is_placeable(tile)
+					|| self.reserved_tiles.contains(&tile)
+					|| (needs_creep && !self.has_creep(tile))
+				{
+					return false;
+				}
+			}
+		}
+
+		!(data.race.is_protoss()
+			&& !matches!(building, UnitTypeId::Pylon | UnitTypeId::Nexus)
+			&& !self.has_power(pos))
+	}
+	/// Returns bot's ground units standing on footprint of given building placed at given position.
+	///
+	/// Such units block construction even if placement query says position is valid,
+	/// because placement query doesn't take units into account, so move them away before building.
+	pub fn placement_blockers(&self, building: UnitTypeId, at: Point2) -> Units {
+		let (w, h) = self.building_size(building);
+		let (half_w, half_h) = (w as f32 / 2.0, h as f32 / 2.0);
+		self.units.my.units.filter(|u| {
+			if u.is_flying() {
+				return false;
+			}
+			let pos = u.position();
+			let closest = Point2::new(
+				pos.x.clamp(at.x - half_w, at.x + half_w),
+				pos.y.clamp(at.y - half_h, at.y + half_h),
+			);
+			pos.distance_squared(closest) < u.radius() * u.radius()
+		})
+	}
+	fn building_tiles(&self, building: UnitTypeId, pos: Point2) -> impl Iterator<Item = Tile> {
+		let (w, h) = self.building_size(building);
+		let x0 = (pos.x - w as f32 / 2.0 + 0.5).floor().max(0.0) as usize;
+		let y0 = (pos.y - h as f32 / 2.0 + 0.5).floor().max(0.0) as usize;
+		(x0..x0 + w as usize).flat_map(move |x| (y0..y0 + h as usize).map(move |y| (x, y)))
+	}
+	/// Marks tiles of given building's footprint on given position as reserved till the end of current step.
+	///
+	/// Reserved tiles are treated as occupied by [`can_place_locally`](Self::can_place_locally)
+	/// and [`find_placement`](Self::find_placement), so several buildings planned on the same step
+	/// won't be placed onto the same spot.
+	pub fn reserve_building(&mut self, building: UnitTypeId, pos: Point2) {
+		let tiles = self.building_tiles(building, pos).collect::<Vec<_>>();
+		self.reserved_tiles.extend(tiles);
+	}
+	/// Checks if footprint of given building on given position overlaps any reserved tile.
+	pub fn is_reserved(&self, building: UnitTypeId, pos: Point2) -> bool {
+		!self.reserved_tiles.is_empty()
+			&& self
+				.building_tiles(building, pos)
+				.any(|tile| self.reserved_tiles.contains(&tile))
+	}
+	/// Returns tiles reserved on current step.
+	pub fn reserved_tiles(&self) -> &FxHashSet<Tile> {
+		&self.reserved_tiles
+	}
+	/// Returns placement size of given building in tiles as `(width, height)`.
+	///
+	/// Taken from [`BUILDING_FOOTPRINTS`], falls back to game data for types missing there.
+	/// Returns `(0, 0)` for units which can't be placed.
+	pub fn building_size(&self, building: UnitTypeId) -> (u32, u32) {
+		let size = match BUILDING_FOOTPRINTS.get(&building) {
+			Some(size) => *size,
+			None => self
+				.game_data
+				.units
+				.get(&building)
+				.and_then(|data| data.ability)
+				.and_then(|a| self.game_data.abilities.get(&a))
+				.and_then(|a| a.footprint_radius)
+				.map_or(0, |radius| (radius * 2.0) as u32),
+		};
+		(size, size)
+	}
 	/// Simple wrapper around [`query_placement`](Self::query_placement).
 	/// Multi-version of [`can_place`](Self::can_place).
 	pub fn can_place_some(&self, places: Vec<(UnitTypeId, Point2)>) -> Vec<bool> {
@@ -1526,6 +3411,8 @@ impl Bot {
 	/// Nice wrapper around [`query_placement`](Self::query_placement).
 	/// Returns correct position where it is possible to build given `building`,
 	/// or `None` if position is not found or `building` can't be built by a worker.
+	///
+	/// Positions overlapping tiles reserved with [`reserve_building`](Self::reserve_building) are skipped.
 	pub fn find_placement(
 		&self,
 		building: UnitTypeId,
@@ -1535,21 +3422,22 @@ impl Bot {
 		if let Some(data) = self.game_data.units.get(&building) {
 			if let Some(ability) = data.ability {
 				let addon = options.addon;
-				if self
-					.query_placement(
-						if addon {
-							vec![
-								(ability, near, None),
-								(AbilityId::TerranBuildSupplyDepot, near.offset(2.5, -0.5), None),
-							]
-						} else {
-							vec![(ability, near, None)]
-						},
-						false,
-					)
-					.unwrap()
-					.iter()
-					.all(|r| matches!(r, ActionResult::Success))
+				if !self.is_reserved(building, near)
+					&& self
+						.query_placement(
+							if addon {
+								vec![
+									(ability, near, None),
+									(AbilityId::TerranBuildSupplyDepot, near.offset(2.5, -0.5), None),
+								]
+							} else {
+								vec![(ability, near, None)]
+							},
+							false,
+						)
+						.unwrap()
+						.iter()
+						.all(|r| matches!(r, ActionResult::Success))
 				{
 					return Some(near);
 				}
@@ -1566,6 +3454,7 @@ impl Bot {
 								near.offset(distance as f32, offset as f32),
 							]
 						})
+						.filter(|pos| !self.is_reserved(building, *pos))
 						.collect::<Vec<Point2>>();
 					let results = self
 						.query_placement(positions.iter().map(|pos| (ability, *pos, None)).collect(), false)
@@ -1605,7 +3494,7 @@ impl Bot {
 
 					if !valid_positions.is_empty() {
 						return if options.random {
-							valid_positions.choose(&mut thread_rng()).copied()
+							valid_positions.choose(&mut *self.rng.write_lock()).copied()
 						} else {
 							valid_positions.iter().closest(near).copied()
 						};
@@ -1615,6 +3504,25 @@ impl Bot {
 		}
 		None
 	}
+	/// Returns position of add-on (Reactor or Tech Lab) for given terran production building.
+	///
+	/// Add-on is placed to the right of building: its center is offset by `(2.5, -0.5)`.
+	pub fn addon_position(&self, building: &Unit) -> Point2 {
+		building.position().offset(2.5, -0.5)
+	}
+	/// Checks if add-on can be built for given building right now.
+	///
+	/// Building must be ready, landed Barracks, Factory or Starport without add-on,
+	/// and [`addon_position`](Self::addon_position) must be free
+	/// (checked with [`can_place`](Self::can_place) of 2x2 Supply Depot there, like the game does).
+	pub fn can_build_addon(&self, building: &Unit) -> bool {
+		matches!(
+			building.type_id(),
+			UnitTypeId::Barracks | UnitTypeId::Factory | UnitTypeId::Starport
+		) && building.is_ready()
+			&& !building.has_addon()
+			&& self.can_place(UnitTypeId::SupplyDepot, self.addon_position(building))
+	}
 	/// Another wrapper around [`query_placement`](Self::query_placement),
 	/// used to find free geyser near given base.
 	///
@@ -1642,6 +3550,52 @@ impl Bot {
 	pub fn get_expansion(&self) -> Option<&Expansion> {
 		self.expansions.iter().find(|exp| exp.alliance.is_neutral())
 	}
+	/// Takes the next free expansion: chooses worker and orders it to build townhall there.
+	///
+	/// Expansions are checked in order of [`expansions`](Self::expansions) (closest to bot's start first),
+	/// ones with enemy units or structures near them and ones where townhall
+	/// [can't be placed](Self::can_place) are skipped.
+	/// Worker is chosen with [`available_builder`](Self::available_builder)
+	/// or the closest one if there're no available builders.
+	///
+	/// Returns tag of the worker or `None` if bot can't afford townhall, there're no free expansions,
+	/// workers or some worker is already going to build townhall.
+	pub fn expand(&mut self) -> Option<u64> {
+		let townhall = self.race_values.start_townhall;
+		if !self.can_afford(townhall, false) {
+			return None;
+		}
+		let ability = self.game_data.units.get(&townhall)?.ability?;
+		if self
+			.units
+			.my
+			.workers
+			.iter()
+			.any(|w| w.ordered_ability() == Some(ability))
+		{
+			return None;
+		}
+
+		let enemies = &self.units.enemy.all;
+		let loc = self
+			.free_expansions()
+			.map(|exp| exp.loc)
+			.filter(|loc| {
+				!enemies
+					.iter()
+					.any(|e| !e.is_snapshot() && e.is_closer(10.0, *loc))
+			})
+			.find(|loc| self.can_place(townhall, *loc))?;
+
+		let worker = match self.available_builder(loc) {
+			Some(worker) => worker,
+			None => self.units.my.workers.closest(loc)?,
+		};
+		worker.build(townhall, loc, false);
+		let tag = worker.tag();
+		self.subtract_resources(townhall, false);
+		Some(tag)
+	}
 	/// Returns next possible location from [`expansions`](Self::expansions) closest to
 	/// opponent's start location or `None` if there aren't any free locations.
 	pub fn get_enemy_expansion(&self) -> Option<&Expansion> {
@@ -1658,6 +3612,57 @@ impl Bot {
 			.min_by(|(_, path1), (_, path2)| path1.partial_cmp(path2).unwrap())
 			.map(|(exp, _)| exp)
 	}
+	/// Returns location of expansion closest to given position or `None` if there're no expansions.
+	pub fn closest_expansion_to(&self, pos: Point2) -> Option<Point2> {
+		self.expansions.iter().map(|exp| exp.loc).closest(pos)
+	}
+	/// Returns location of opponent's natural expansion, i.e. expansion closest to opponent's start
+	/// (excluding start itself) or `None` if there're no expansions.
+	///
+	/// Uses [`known_enemy_start`](Self::known_enemy_start) if it was confirmed,
+	/// otherwise [`enemy_start`](Self::enemy_start).
+	/// Distance is straight, so on some maps it's better to check path distance.
+	pub fn enemy_natural(&self) -> Option<Point2> {
+		let start = self.known_enemy_start.unwrap_or(self.enemy_start);
+		self.expansions
+			.iter()
+			.map(|exp| exp.loc)
+			.filter(|loc| loc.is_further(1.0, start))
+			.closest(start)
+	}
+	/// Returns location of free expansion, which is the best to deny by blocking it with a building.
+	///
+	/// Candidates are free expansions closer to opponent's bases than to bot's start location.
+	/// Score of each candidate is straight distance to the closest enemy base plus `5` for each supply
+	/// of enemy army within `15` distance of it, candidates with `4` or more enemy army supply
+	/// near them are skipped. Returns candidate with the lowest score or `None` if there're no candidates.
+	pub fn best_expansion_to_deny(&self) -> Option<Point2> {
+		let mut bases = self.enemy_expansions().map(|exp| exp.loc).collect::<Vec<_>>();
+		if bases.is_empty() {
+			bases.push(self.known_enemy_start.unwrap_or(self.enemy_start));
+		}
+		let enemies = &self.units.enemy.units;
+
+		self.free_expansions()
+			.filter_map(|exp| {
+				let loc = exp.loc;
+				let distance = bases.iter().copied().closest_distance(loc)?;
+				if distance >= loc.distance(self.start_location) {
+					return None;
+				}
+				let defense = enemies
+					.iter()
+					.filter(|u| !u.is_worker() && u.is_closer(15.0, loc))
+					.map(|u| u.supply_cost())
+					.sum::<f32>();
+				if defense >= 4.0 {
+					return None;
+				}
+				Some((loc, distance + defense * 5.0))
+			})
+			.min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+			.map(|(loc, _)| loc)
+	}
 	/// Returns all [`expansions`](Self::expansions) taken by bot.
 	pub fn owned_expansions(&self) -> impl Iterator<Item = &Expansion> {
 		self.expansions.iter().filter(|exp| exp.alliance.is_mine())
@@ -1670,6 +3675,49 @@ impl Bot {
 	pub fn free_expansions(&self) -> impl Iterator<Item = &Expansion> {
 		self.expansions.iter().filter(|exp| exp.alliance.is_neutral())
 	}
+	/// Finds path for ground units between given positions using A* over local pathing grid.
+	/// Both positions should be pathable (note that initial structures are marked as not pathable).
+	///
+	/// Unlike [`query_pathing`](Self::query_pathing) doesn't send requests to API,
+	/// but pathing grid doesn't include units, so path may be blocked by them.
+	/// Use [`astar`] directly to customize costs or disable diagonal moves.
+	///
+	/// Returns centers of tiles on the path from `start` to `goal` or `None` if there's no path.
+	pub fn find_path(&self, start: Point2, goal: Point2) -> Option<Vec<Point2>> {
+		let grid = &self.game_info.pathing_grid;
+		astar(
+			start.into(),
+			goal.into(),
+			|pos| grid.get(pos).filter(|p| p.is_empty()).map(|_| 1.0),
+			true,
+		)
+		.map(|path| path.into_iter().map(Point2::from).collect())
+	}
+	/// Returns length of the path for ground units between given positions
+	/// or `None` if there's no path.
+	///
+	/// Path is calculated locally with [`find_path`](Self::find_path)
+	/// and cached by rounded tile positions of its ends until the end of the game,
+	/// so it's cheap to request the same distances every step.
+	pub fn path_distance(&mut self, from: Point2, to: Point2) -> Option<f32> {
+		let key = (Tile::from(from), Tile::from(to));
+		if let Some(distance) = self.path_distances.get(&key) {
+			return *distance;
+		}
+		let distance = self.find_path(from, to).map(|path| {
+			path.iter()
+				.zip(path.iter().skip(1))
+				.map(|(p0, p1)| p0.distance(*p1))
+				.sum()
+		});
+		self.path_distances.insert(key, distance);
+		distance
+	}
+	/// Clears cache of [`path_distance`](Self::path_distance),
+	/// should be called if pathing grid was changed (e.g. rocks were destroyed).
+	pub fn clear_path_distances(&mut self) {
+		self.path_distances.clear();
+	}
 	/// Sends pathing requests to API.
 	///
 	/// Takes `Vec` of (start, goal), where `start` is position or unit tag and `goal` is position.
@@ -1740,16 +3788,193 @@ impl Bot {
 			.collect())
 	}
 
+	/// Basic micro for ranged units: attacks when weapon is ready, kites otherwise.
+	///
+	/// If weapon is off cooldown and some of given enemies are in range,
+	/// attacks the one with the least hits left.
+	/// Otherwise moves relative to the closest enemy:
+	/// - closes the distance if that enemy out-ranges the unit;
+	/// - steps back to the edge of unit's range while weapon is cooling down,
+	///   and approaches again when it's almost ready or can't return in range in time.
+	///
+	/// Uses [`real_range_vs`], [`cooldown_percentage`] and [`real_speed`] in calculations.
+	///
+	/// [`real_range_vs`]: Unit::real_range_vs
+	/// [`cooldown_percentage`]: Unit::cooldown_percentage
+	/// [`real_speed`]: Unit::real_speed
+	pub fn stutter_step(&mut self, unit: &Unit, enemies: &Units) {
+		if !unit.on_cooldown() {
+			if let Some(target) = enemies
+				.iter()
+				.filter(|e| e.can_be_attacked() && unit.can_attack_unit(e) && unit.in_real_range(e, 0.0))
+				.min_by_key(|e| e.hits().unwrap_or(0))
+			{
+				unit.attack(Target::Tag(target.tag()), false);
+				return;
+			}
+		}
+
+		let closest = match enemies.closest(unit) {
+			Some(closest) => closest,
+			None => return,
+		};
+		let range = unit.real_range_vs(closest);
+		if closest.real_range_vs(unit) >= range {
+			unit.move_to(Target::Pos(closest.position()), false);
+			return;
+		}
+
+		// Approach when weapon is almost ready or unit is too far to return in range in time
+		let pos = closest.position();
+		let gap = unit.radius() + closest.radius() + range;
+		if matches!(unit.cooldown_percentage(), Some(cooldown) if cooldown < 0.5)
+			|| unit.distance(pos) - gap > unit.distance_to_weapon_ready()
+		{
+			unit.move_to(Target::Pos(pos), false);
+		} else {
+			unit.move_to(Target::Pos(pos.towards(unit.position(), gap)), false);
+		}
+	}
+	/// Finds position for area of effect spell (Psi Storm, Fungal Growth, EMP, Corrosive Bile, ...)
+	/// with given `radius`, which hits the most of given enemies.
+	///
+	/// Candidate centers are positions of enemies and centers of groups around them.
+	/// Returns position with number of enemies hit there,
+	/// or `None` if less than `min_hits` enemies would be hit.
+	pub fn best_aoe_target(&self, enemies: &Units, radius: f32, min_hits: usize) -> Option<(Point2, usize)> {
+		let count_hits = |pos: Point2| enemies.iter().filter(|e| e.is_closer(radius, pos)).count();
+		enemies
+			.iter()
+			.flat_map(|e| {
+				let pos = e.position();
+				let group_center = enemies.iter().filter(|u| u.is_closer(radius, pos)).center();
+				std::iter::once(pos).chain(group_center)
+			})
+			.map(|pos| (pos, count_hits(pos)))
+			.filter(|(_, hits)| *hits >= min_hits.max(1))
+			.max_by_key(|(_, hits)| *hits)
+	}
+	/// Returns position where `caster` should aim to hit moving `target` with projectile
+	/// (or spell delay) of given speed (distance per second).
+	///
+	/// Target is assumed to keep moving in a straight line with constant speed,
+	/// see [`Unit::predicted_position`], targets with unknown motion are treated as stationary.
+	/// If target can't be intercepted, returns its current position.
+	pub fn lead_target(&self, caster: &Unit, target: &Unit, projectile_speed: f32) -> Point2 {
+		let pos = target.position();
+		let velocity = target.predicted_position(FRAMES_PER_SECOND) - pos;
+		let offset = pos - caster.position();
+
+		// Solving |offset + velocity * t| = projectile_speed * t for the smallest positive t
+		let a = velocity.len_squared() - projectile_speed * projectile_speed;
+		let b = 2.0 * (offset.x * velocity.x + offset.y * velocity.y);
+		let c = offset.len_squared();
+		let time = if a.abs() < f32::EPSILON {
+			if b < 0.0 {
+				Some(-c / b)
+			} else {
+				None
+			}
+		} else {
+			let discriminant = b * b - 4.0 * a * c;
+			if discriminant < 0.0 {
+				None
+			} else {
+				let root = discriminant.sqrt();
+				[(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+					.iter()
+					.copied()
+					.filter(|t| *t > 0.0)
+					.min_by(|t1, t2| t1.partial_cmp(t2).unwrap())
+			}
+		};
+
+		match time {
+			Some(time) => target.predicted_position(time * FRAMES_PER_SECOND),
+			None => pos,
+		}
+	}
+	/// Chooses which of given enemies unit should attack to focus fire.
+	///
+	/// Prefers enemies in range with the least hits left, otherwise returns the closest enemy
+	/// unit can attack, or `None` if there're no such enemies.
+	pub fn focus_target<'a>(&self, unit: &Unit, enemies: &'a Units) -> Option<&'a Unit> {
+		let targets = enemies
+			.iter()
+			.filter(|e| e.can_be_attacked() && unit.can_attack_unit(e))
+			.collect::<Vec<_>>();
+		targets
+			.iter()
+			.copied()
+			.filter(|e| unit.in_real_range(e, 0.0))
+			.min_by_key(|e| e.hits().unwrap_or(0))
+			.or_else(|| targets.iter().copied().closest(unit))
+	}
+	/// Defends given position with army.
+	///
+	/// If there're enemies within `radius` of position, every army unit attacks
+	/// its [focus target](Self::focus_target) among them. Enemies outside of radius are ignored,
+	/// so units don't pursue fleeing enemies too far and return when there's no one left.
+	/// Otherwise units which are further than `radius / 2` from position are rallied back to it.
+	///
+	/// Units that are already doing the right thing aren't commanded again.
+	pub fn defend(&mut self, army: &Units, position: Point2, radius: f32) {
+		let enemies = self
+			.query_radius(position, radius)
+			.filter(|u| u.is_enemy() && !u.is_hallucination() && u.is_visible());
+
+		for unit in army {
+			match self.focus_target(unit, &enemies) {
+				Some(target) => {
+					if unit.target_tag() != Some(target.tag()) {
+						unit.attack(Target::Tag(target.tag()), false);
+					}
+				}
+				None => {
+					if !unit.is_closer(radius / 2.0, position) && unit.target_pos() != Some(position) {
+						unit.move_to(Target::Pos(position), false);
+					}
+				}
+			}
+		}
+	}
+	/// Moves given units toward safe position, while units which can fire keep shooting back.
+	///
+	/// Unit attacks [focus target](Self::focus_target) if its weapon is off cooldown
+	/// and there're enemies in its range, otherwise it moves to `to`.
+	/// Units already moving to `to` aren't given another order.
+	///
+	/// Note: units move straight to `to`, path isn't adjusted to avoid enemies.
+	pub fn retreat(&mut self, units: &Units, to: Point2) {
+		for unit in units {
+			if !unit.on_cooldown() {
+				let enemies = self
+					.enemies_in_range(unit, 0.0)
+					.filter(|u| !u.is_hallucination() && u.is_visible());
+				if let Some(target) = self.focus_target(unit, &enemies) {
+					if unit.target_tag() != Some(target.tag()) {
+						unit.attack(Target::Tag(target.tag()), false);
+					}
+					continue;
+				}
+			}
+			if unit.target_pos() != Some(to) {
+				unit.move_to(Target::Pos(to), false);
+			}
+		}
+	}
+
 	/// Leaves current game, which is counted as Defeat for bot.
 	///
-	/// Note: [`on_end`] will not be called, if needed use [`debug.end_game`] instead.
+	/// Note: [`on_end`] will not be called, if needed use [`leave_game`](Self::leave_game)
+	/// or [`debug.end_game`] instead.
 	///
 	/// [`on_end`]: crate::Player::on_end
 	/// [`debug.end_game`]: Debugger::end_game
 	pub fn leave(&self) -> SC2Result<()> {
 		let mut req = Request::new();
 		req.mut_leave_game();
-		self.api().send_request(req)
+		Ok(self.api().send_request(req)?)
 	}
 
 	pub(crate) fn close_client(&mut self) {
@@ -1816,6 +4041,8 @@ impl Default for Bot {
 			expansions: Default::default(),
 			max_cooldowns: Default::default(),
 			last_units_health: Default::default(),
+			last_units_position: Default::default(),
+			last_units_loop: 0,
 			vision_blockers: Default::default(),
 			ramps: Default::default(),
 			enemy_upgrades: Default::default(),
@@ -1825,6 +4052,25 @@ impl Default for Bot {
 			enemies_current: Default::default(),
 			saved_hallucinations: Default::default(),
 			available_frames: Default::default(),
+			rng: Rl::new(StdRng::from_entropy()),
+			path_distances: Default::default(),
+			possible_enemy_starts: Default::default(),
+			known_enemy_start: None,
+			leave_requested: false,
+			ability_casts: Default::default(),
+			step_time: 0.0,
+			profiling: false,
+			profile: Default::default(),
+			spatial: LazyInit::new(),
+			attackers: Default::default(),
+			reserved_tiles: Default::default(),
+			apm_limit: None,
+			game_version: String::new(),
+			data_dir: PathBuf::from("data"),
+			realtime: None,
+			reconnect: false,
+			action_log: Default::default(),
+			rush_options: Default::default(),
 		}
 	}
 }
@@ -1834,3 +4080,162 @@ impl Drop for Bot {
 		self.close_client();
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		game_data::UnitTypeData,
+		unit::{
+			tests::{make_unit, proto_unit},
+			DataForUnit,
+		},
+	};
+
+	fn unit_data(id: UnitTypeId, race: Race, require_attached: bool) -> UnitTypeData {
+		UnitTypeData {
+			id,
+			name: format!("{:?}", id),
+			available: true,
+			cargo_size: 2,
+			mineral_cost: 100,
+			vespene_cost: 25,
+			food_required: 2.0,
+			food_provided: 0.0,
+			ability: None,
+			race,
+			build_time: 300.0,
+			has_vespene: false,
+			has_minerals: false,
+			sight_range: 10.0,
+			tech_alias: vec![],
+			unit_alias: None,
+			tech_requirement: None,
+			require_attached,
+			attributes: vec![],
+			movement_speed: 3.15,
+			armor: 1,
+			weapons: vec![],
+		}
+	}
+	fn test_bot() -> Bot {
+		let mut game_data = GameData::default();
+		for data in [
+			unit_data(UnitTypeId::Marauder, Race::Terran, true),
+			unit_data(UnitTypeId::Zealot, Race::Protoss, false),
+		] {
+			game_data.units.insert(data.id, data);
+		}
+		let mut bot = Bot::default();
+		bot.game_data = Rs::new(game_data);
+		bot.minerals = 1000;
+		bot.vespene = 1000;
+		bot.supply_left = 10;
+		bot.data_for_unit = Rs::new(DataForUnit {
+			game_data: Rs::clone(&bot.game_data),
+			techlab_tags: Rs::clone(&bot.techlab_tags),
+			reactor_tags: Rs::clone(&bot.reactor_tags),
+			abilities_units: Rs::clone(&bot.abilities_units),
+			..Default::default()
+		});
+		bot
+	}
+
+	#[test]
+	fn can_train_requires_techlab() {
+		let bot = test_bot();
+		let barracks = make_unit(
+			&bot.data_for_unit,
+			&proto_unit(1, UnitTypeId::Barracks, 20.5, 20.5),
+		);
+		assert!(!bot.can_train(&barracks, UnitTypeId::Marauder));
+
+		let mut proto = proto_unit(2, UnitTypeId::Barracks, 30.5, 20.5);
+		proto.set_add_on_tag(3);
+		bot.techlab_tags.write_lock().insert(3);
+		let barracks = make_unit(&bot.data_for_unit, &proto);
+		assert!(bot.can_train(&barracks, UnitTypeId::Marauder));
+		assert!(!bot.can_train(&barracks, UnitTypeId::Zealot));
+	}
+
+	#[test]
+	fn can_train_warp_in() {
+		let bot = test_bot();
+		let gateway = make_unit(
+			&bot.data_for_unit,
+			&proto_unit(1, UnitTypeId::Gateway, 20.5, 20.5),
+		);
+		assert!(bot.can_train(&gateway, UnitTypeId::Zealot));
+
+		let warpgate = make_unit(
+			&bot.data_for_unit,
+			&proto_unit(2, UnitTypeId::WarpGate, 30.5, 20.5),
+		);
+		assert!(!bot.can_train(&warpgate, UnitTypeId::Zealot));
+
+		bot.abilities_units
+			.write_lock()
+			.insert(2, [AbilityId::WarpGateTrainZealot].into_iter().collect());
+		assert!(bot.can_train(&warpgate, UnitTypeId::Zealot));
+		assert!(!bot.can_train(&warpgate, UnitTypeId::Marauder));
+	}
+
+	#[test]
+	fn addon_position_offset() {
+		let bot = test_bot();
+		let barracks = make_unit(
+			&bot.data_for_unit,
+			&proto_unit(1, UnitTypeId::Barracks, 40.5, 30.5),
+		);
+		assert_eq!(bot.addon_position(&barracks), Point2::new(43.0, 30.0));
+	}
+
+	#[test]
+	fn reset_for_new_game_clears_state() {
+		let mut bot = test_bot();
+		let barracks = make_unit(
+			&bot.data_for_unit,
+			&proto_unit(1, UnitTypeId::Barracks, 40.5, 30.5),
+		);
+		bot.units.my.structures.push(barracks.clone());
+		bot.units.all.push(barracks);
+		bot.owned_tags.insert(1);
+		bot.under_construction.insert(1);
+		bot.saved_hallucinations.insert(2);
+		bot.enemy_upgrades.write_lock().insert(UpgradeId::Stimpack);
+		bot.techlab_tags.write_lock().insert(3);
+		bot.reactor_tags.write_lock().insert(4);
+		bot.max_cooldowns.write_lock().insert(UnitTypeId::Marine, 15.0);
+		bot.last_units_health.write_lock().insert(1, 1000);
+		bot.last_units_position
+			.write_lock()
+			.insert(1, (Point2::new(40.5, 30.5), 100));
+		bot.last_units_loop = 100;
+		bot.available_frames.write_lock().insert(1, 100);
+		bot.profile.insert("section".to_string(), Default::default());
+		bot.reserved_tiles.insert((10, 10));
+		bot.ability_casts.insert((1, AbilityId::EffectStim), 100);
+		bot.action_log.push_back((100, 5));
+		bot.leave_requested = true;
+
+		bot.reset_for_new_game();
+
+		assert!(bot.units.all.is_empty() && bot.units.my.structures.is_empty());
+		assert!(bot.owned_tags.is_empty());
+		assert!(bot.under_construction.is_empty());
+		assert!(bot.saved_hallucinations.is_empty());
+		assert!(bot.enemy_upgrades.read_lock().is_empty());
+		assert!(bot.techlab_tags.read_lock().is_empty());
+		assert!(bot.reactor_tags.read_lock().is_empty());
+		assert!(bot.max_cooldowns.read_lock().is_empty());
+		assert!(bot.last_units_health.read_lock().is_empty());
+		assert!(bot.last_units_position.read_lock().is_empty());
+		assert_eq!(bot.last_units_loop, 0);
+		assert!(bot.available_frames.read_lock().is_empty());
+		assert!(bot.profile.is_empty());
+		assert!(bot.reserved_tiles.is_empty());
+		assert!(bot.ability_casts.is_empty());
+		assert!(bot.action_log.is_empty());
+		assert!(!bot.leave_requested);
+	}
+}
@@ -0,0 +1,154 @@
+//! Tracking of scripted build orders.
+//!
+//! [`BuildOrder`] doesn't give any commands by itself, it only keeps track of which steps are done,
+//! so bot can see what to build next and switch to reactive play once the build finishes.
+
+use crate::{
+	bot::Bot,
+	consts::TECH_REQUIREMENTS,
+	ids::{UnitTypeId, UpgradeId},
+};
+
+/// What is built on a step of build order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BuildItem {
+	/// Unit or structure of given type.
+	Unit(UnitTypeId),
+	/// Upgrade.
+	Upgrade(UpgradeId),
+}
+
+/// Single step of [`BuildOrder`].
+#[derive(Debug, Clone)]
+pub struct BuildStep {
+	/// What should be built.
+	pub item: BuildItem,
+	/// How many units of this type bot should have (including ordered ones) after this step.
+	/// Ignored for upgrades.
+	pub count: usize,
+	/// Supply at which step should be started, `None` if step can be started right away.
+	pub supply: Option<u32>,
+}
+impl BuildStep {
+	/// Constructs step which orders given unit type until bot has `count` of them.
+	pub fn unit(unit: UnitTypeId, count: usize) -> Self {
+		Self {
+			item: BuildItem::Unit(unit),
+			count,
+			supply: None,
+		}
+	}
+	/// Constructs step which researches given upgrade.
+	pub fn upgrade(upgrade: UpgradeId) -> Self {
+		Self {
+			item: BuildItem::Upgrade(upgrade),
+			count: 1,
+			supply: None,
+		}
+	}
+	/// Sets supply at which step should be started.
+	pub fn at_supply(mut self, supply: u32) -> Self {
+		self.supply = Some(supply);
+		self
+	}
+
+	/// Checks if step is done, i.e. unit or upgrade is already ordered or complete.
+	pub fn is_done(&self, bot: &Bot) -> bool {
+		match self.item {
+			BuildItem::Unit(unit) => bot.counter().all().count(unit) >= self.count,
+			BuildItem::Upgrade(upgrade) => bot.has_upgrade(upgrade) || bot.is_ordered_upgrade(upgrade),
+		}
+	}
+	/// Returns what currently prevents this step from being started, or `None` if it can be started.
+	pub fn blocker(&self, bot: &Bot) -> Option<StepBlocker> {
+		if let Some(supply) = self.supply {
+			if bot.supply_used < supply {
+				return Some(StepBlocker::SupplyTiming);
+			}
+		}
+		match self.item {
+			BuildItem::Unit(unit) => {
+				if let Some(requirement) = TECH_REQUIREMENTS.get(&unit) {
+					if bot.counter().tech().count(*requirement) == 0 {
+						return Some(StepBlocker::Tech);
+					}
+				}
+				if !bot.can_afford(unit, false) {
+					return Some(StepBlocker::Resources);
+				}
+				if !bot.can_afford(unit, true) {
+					return Some(StepBlocker::Supply);
+				}
+			}
+			BuildItem::Upgrade(upgrade) => {
+				if !bot.can_afford_upgrade(upgrade) {
+					return Some(StepBlocker::Resources);
+				}
+			}
+		}
+		None
+	}
+}
+
+/// Reason why step of build order can't be started right now.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StepBlocker {
+	/// Bot didn't reach supply at which step should be started.
+	SupplyTiming,
+	/// Required tech structure is missing.
+	Tech,
+	/// Not enough minerals or vespene.
+	Resources,
+	/// Not enough free supply (supply block).
+	Supply,
+}
+
+/// Ordered list of [`BuildStep`]s with tracking of completed ones.
+///
+/// Call [`update`](Self::update) every step to advance it.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOrder {
+	steps: Vec<BuildStep>,
+	completed: usize,
+}
+impl BuildOrder {
+	/// Constructs build order from given steps.
+	pub fn new(steps: Vec<BuildStep>) -> Self {
+		Self { steps, completed: 0 }
+	}
+	/// Adds step to the end of build order.
+	pub fn push(&mut self, step: BuildStep) {
+		self.steps.push(step);
+	}
+	/// Returns all steps of build order.
+	pub fn steps(&self) -> &[BuildStep] {
+		&self.steps
+	}
+
+	/// Marks consecutive done steps as completed, starting from the current one.
+	pub fn update(&mut self, bot: &Bot) {
+		while let Some(step) = self.steps.get(self.completed) {
+			if !step.is_done(bot) {
+				break;
+			}
+			self.completed += 1;
+		}
+	}
+	/// Returns number of completed steps and total number of steps.
+	pub fn progress(&self) -> (usize, usize) {
+		(self.completed, self.steps.len())
+	}
+	/// Returns first not completed step or `None` if build order is complete.
+	pub fn current_step(&self) -> Option<&BuildStep> {
+		self.steps.get(self.completed)
+	}
+	/// Checks if all steps are completed.
+	pub fn is_complete(&self) -> bool {
+		self.completed >= self.steps.len()
+	}
+	/// Returns what blocks the current step, useful for debugging stalls.
+	/// See [`BuildStep::blocker`].
+	pub fn blocker(&self, bot: &Bot) -> Option<StepBlocker> {
+		self.current_step()?.blocker(bot)
+	}
+}
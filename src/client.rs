@@ -8,7 +8,7 @@ use crate::{
 	bot::{Bot, LockOwned, Rs},
 	game_state::update_state,
 	paths::*,
-	player::Computer,
+	player::{Computer, GameResult},
 	IntoProto, IntoSC2, Player, PlayerSettings,
 };
 use sc2_proto::sc2api::{PlayerSetup, PlayerType, PortSet, Request, RequestCreateGame, Status};
@@ -16,14 +16,16 @@ use std::{
 	error::Error,
 	fmt,
 	fs::File,
-	io::Write,
+	io::{self, Write},
 	net::{TcpListener, TcpStream},
 	ops::{Deref, DerefMut},
 	process::{Child, Command},
+	time::{Duration, Instant},
 };
 use tungstenite::{connect, stream::MaybeTlsStream, WebSocket};
 
 pub(crate) type WS = WebSocket<MaybeTlsStream<TcpStream>>;
+
 pub type SC2Result<T> = Result<T, Box<dyn Error>>;
 
 #[cfg(all(feature = "wine_sc2", not(target_os = "linux")))]
@@ -82,6 +84,8 @@ where
 	pub realtime: bool,
 	/// Save replay after the game in given path.
 	pub save_replay_as: Option<&'a str>,
+	/// Seed for game's random number generator, makes games reproducible.
+	pub random_seed: Option<u32>,
 }
 
 impl<'a, B> RunnerSingle<'a, B>
@@ -102,6 +106,7 @@ where
 			map_path,
 			save_replay_as: None,
 			realtime: false,
+			random_seed: None,
 		}
 	}
 
@@ -111,12 +116,12 @@ where
 		debug!("Launching SC2 process");
 		self.bot.process = Some(launch_client(&self.sc2_path, port, self.sc2_version));
 		debug!("Connecting to websocket");
-		self.bot.api = Some(API::new(connect_to_websocket(HOST, port)?));
+		self.bot.api = Some(connect_to_websocket(HOST, port)?);
 		Ok(())
 	}
 
-	/// Runs requested game.
-	pub fn run_game(&mut self) -> SC2Result<()> {
+	/// Runs requested game and returns it's result for bot.
+	pub fn run_game(&mut self) -> SC2Result<GameResult> {
 		let settings = self.bot.get_player_settings();
 		let api = self.bot.api();
 
@@ -130,7 +135,13 @@ where
 		create_player_setup(&settings, req_create_game);
 		create_computer_setup(&self.computer, req_create_game);
 
+		if let Some(realtime) = self.bot.realtime {
+			self.realtime = realtime;
+		}
 		req_create_game.set_realtime(self.realtime);
+		if let Some(seed) = self.random_seed {
+			req_create_game.set_random_seed(seed);
+		}
 
 		let res = api.send(req)?;
 		let res_create_game = res.get_create_game();
@@ -141,7 +152,7 @@ where
 				res_create_game.get_error_details()
 			);
 			error!("{}", err);
-			panic!("{}", err);
+			return Err(SC2Error::InvalidResponse(err).into());
 		}
 
 		debug!("Sending JoinGame request");
@@ -153,15 +164,21 @@ where
 		debug!("Entered main loop");
 		play_first_step(self.bot, self.realtime)?;
 		let mut iteration = 0;
-		while play_step(self.bot, iteration, self.realtime)? {
+		let result = loop {
+			if let Some(result) = play_step(self.bot, iteration, self.realtime, self.save_replay_as)? {
+				break result;
+			}
 			iteration += 1;
-		}
+		};
 		debug!("Game finished");
 
-		if let Some(path) = &self.save_replay_as {
-			save_replay(self.bot.api(), path)?;
+		// Replay is already saved before leaving the game
+		if !self.bot.leave_requested {
+			if let Some(path) = &self.save_replay_as {
+				save_replay(self.bot.api(), path)?;
+			}
 		}
-		Ok(())
+		Ok(result)
 	}
 
 	/// Changes map to play on.
@@ -194,6 +211,8 @@ where
 	pub realtime: bool,
 	/// Save replay after the game in given path.
 	pub save_replay_as: Option<&'a str>,
+	/// Seed for game's random number generator, makes games reproducible.
+	pub random_seed: Option<u32>,
 }
 
 impl<'a, B> RunnerMulti<'a, B>
@@ -220,6 +239,7 @@ where
 			map_path,
 			save_replay_as: None,
 			realtime: false,
+			random_seed: None,
 		}
 	}
 
@@ -235,15 +255,15 @@ where
 		self.bot.process = Some(launch_client(&self.sc2_path, port_bot, self.sc2_version));
 
 		debug!("Connecting to host websocket");
-		self.human.api = Some(API::new(connect_to_websocket(HOST, port_human)?));
+		self.human.api = Some(connect_to_websocket(HOST, port_human)?);
 		debug!("Connecting to client websocket");
-		self.bot.api = Some(API::new(connect_to_websocket(HOST, port_bot)?));
+		self.bot.api = Some(connect_to_websocket(HOST, port_bot)?);
 
 		Ok(())
 	}
 
-	/// Runs requested game.
-	pub fn run_game(&mut self) -> SC2Result<()> {
+	/// Runs requested game and returns it's result for bot.
+	pub fn run_game(&mut self) -> SC2Result<GameResult> {
 		let bot_settings = self.bot.get_player_settings();
 		let human_api = self.human.api.as_ref().unwrap();
 
@@ -256,7 +276,13 @@ where
 			.set_map_path(self.map_path.clone());
 		create_player_setup(&self.human_settings, req_create_game);
 		create_player_setup(&bot_settings, req_create_game);
+		if let Some(realtime) = self.bot.realtime {
+			self.realtime = realtime;
+		}
 		req_create_game.set_realtime(self.realtime);
+		if let Some(seed) = self.random_seed {
+			req_create_game.set_random_seed(seed);
+		}
 
 		let res = human_api.send(req)?;
 		let res_create_game = res.get_create_game();
@@ -267,7 +293,7 @@ where
 				res_create_game.get_error_details()
 			);
 			error!("{}", err);
-			panic!("{}", err);
+			return Err(SC2Error::InvalidResponse(err).into());
 		}
 
 		debug!("Sending JoinGame request to both processes");
@@ -292,15 +318,21 @@ where
 		debug!("Entered main loop");
 		play_first_step(self.bot, self.realtime)?;
 		let mut iteration = 0;
-		while play_step(self.bot, iteration, self.realtime)? {
+		let result = loop {
+			if let Some(result) = play_step(self.bot, iteration, self.realtime, self.save_replay_as)? {
+				break result;
+			}
 			iteration += 1;
-		}
+		};
 		debug!("Game finished");
 
-		if let Some(path) = &self.save_replay_as {
-			save_replay(self.bot.api(), path)?;
+		// Replay is already saved before leaving the game
+		if !self.bot.leave_requested {
+			if let Some(path) = &self.save_replay_as {
+				save_replay(self.bot.api(), path)?;
+			}
 		}
-		Ok(())
+		Ok(result)
 	}
 
 	/// Changes map to play on.
@@ -351,19 +383,54 @@ impl Drop for Human {
 	}
 }
 
+/// Errors of communication with SC2 client, returned by [`API`] methods.
+///
+/// Other errors are returned as [`SC2Result`], so check for this type with
+/// `err.downcast_ref::<SC2Error>()` to handle specific cases.
 #[derive(Debug)]
-struct ProtoError(String);
-impl ProtoError {
-	fn new<E: fmt::Debug>(error: E, details: &str) -> Self {
-		Self(format!("{:?}: {}", error, details))
-	}
+pub enum SC2Error {
+	/// Websocket connection to SC2 client was closed (e.g. client crashed or was killed).
+	ConnectionClosed,
+	/// Failed to encode request or decode response.
+	ProtobufDecode(String),
+	/// Game has ended, so request can't be processed.
+	GameEnded,
+	/// Unexpected websocket message, or response contains errors (e.g. failed to create or join game).
+	InvalidResponse(String),
+	/// Reading or writing to websocket timed out.
+	Timeout,
 }
-impl fmt::Display for ProtoError {
+impl fmt::Display for SC2Error {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		write!(f, "{}", self.0)
+		match self {
+			SC2Error::ConnectionClosed => write!(f, "Connection to SC2 client closed"),
+			SC2Error::ProtobufDecode(e) => write!(f, "Protobuf error: {}", e),
+			SC2Error::GameEnded => write!(f, "Game has ended"),
+			SC2Error::InvalidResponse(e) => write!(f, "Invalid response: {}", e),
+			SC2Error::Timeout => write!(f, "Connection to SC2 client timed out"),
+		}
+	}
+}
+impl Error for SC2Error {}
+impl From<tungstenite::Error> for SC2Error {
+	fn from(e: tungstenite::Error) -> Self {
+		match e {
+			tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+				SC2Error::ConnectionClosed
+			}
+			tungstenite::Error::Io(e) => match e.kind() {
+				io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => SC2Error::Timeout,
+				_ => SC2Error::ConnectionClosed,
+			},
+			e => SC2Error::InvalidResponse(e.to_string()),
+		}
+	}
+}
+impl From<protobuf::ProtobufError> for SC2Error {
+	fn from(e: protobuf::ProtobufError) -> Self {
+		SC2Error::ProtobufDecode(e.to_string())
 	}
 }
-impl Error for ProtoError {}
 
 struct Ports {
 	// shared: i32,
@@ -380,10 +447,86 @@ pub struct LaunchOptions<'a> {
 	pub save_replay_as: Option<&'a str>,
 	/// Play games in real time mode or not.
 	pub realtime: bool,
+	/// Seed for game's random number generator, makes games reproducible.
+	pub random_seed: Option<u32>,
+}
+
+/// Configuration of single game vs built-in AI for [`run_many`].
+///
+/// Only computer opponents are supported, use [`RunnerMulti`] to play vs other players.
+pub struct ComputerGameConfig {
+	/// Name of the map to play on.
+	pub map: String,
+	/// Computer opponent configuration.
+	pub computer: Computer,
+	/// Play game in real time mode or not.
+	pub realtime: bool,
+}
+
+/// Summary of game played by [`run_many`].
+#[derive(Debug)]
+pub struct GameReport {
+	/// Result of the game for bot or error which interrupted it.
+	pub result: SC2Result<GameResult>,
+	/// Length of the game in game loops.
+	pub game_loop: u32,
+	/// Real time spent on the game.
+	pub duration: Duration,
 }
 
 // Runners
 
+/// Runs given games vs built-in AI one after another, reusing single SC2 process for all of them.
+///
+/// Returns reports of played games in the same order as given configs.
+/// Failed game doesn't stop the batch, its error is recorded in the report
+/// and the next game is started. Only error of launching SC2 is returned directly.
+///
+/// # Panics
+/// Panics if any of the maps doesn't exist in maps directory.
+pub fn run_many<B>(
+	bot: &mut B,
+	games: Vec<ComputerGameConfig>,
+	sc2_version: Option<&str>,
+) -> SC2Result<Vec<GameReport>>
+where
+	B: Player + DerefMut<Target = Bot> + Deref<Target = Bot>,
+{
+	let mut reports = Vec::with_capacity(games.len());
+	let mut games = games.into_iter();
+	let first = match games.next() {
+		Some(game) => game,
+		None => return Ok(reports),
+	};
+
+	let mut runner = RunnerSingle::new(bot, first.computer, &first.map, sc2_version);
+	runner.realtime = first.realtime;
+	runner.launch()?;
+
+	loop {
+		let start = Instant::now();
+		let result = runner.run_game();
+		if let Err(e) = &result {
+			error!("Game failed: {}", e);
+		}
+		reports.push(GameReport {
+			result,
+			game_loop: runner.bot.state.observation.game_loop(),
+			duration: start.elapsed(),
+		});
+
+		match games.next() {
+			Some(game) => {
+				runner.set_map(&game.map);
+				runner.computer = game.computer;
+				runner.realtime = game.realtime;
+			}
+			None => break,
+		}
+	}
+	Ok(reports)
+}
+
 /// Simple function to run game vs built-in AI.
 pub fn run_vs_computer<B>(
 	bot: &mut B,
@@ -398,6 +541,7 @@ where
 	runner.launch()?;
 	runner.realtime = options.realtime;
 	runner.save_replay_as = options.save_replay_as;
+	runner.random_seed = options.random_seed;
 	runner.run_game()?;
 	Ok(())
 }
@@ -416,7 +560,7 @@ where
 	debug!("Starting ladder game");
 
 	debug!("Connecting to websocket");
-	bot.api = Some(API::new(connect_to_websocket(host, port)?));
+	bot.api = Some(connect_to_websocket(host, port)?);
 
 	debug!("Sending JoinGame request");
 
@@ -441,7 +585,7 @@ where
 	// Main loop
 	let mut iteration = 0;
 	play_first_step(bot, false)?;
-	while play_step(bot, iteration, false)? {
+	while play_step(bot, iteration, false, None)?.is_none() {
 		iteration += 1;
 	}
 	debug!("Game finished");
@@ -463,6 +607,7 @@ where
 	runner.launch()?;
 	runner.realtime = options.realtime;
 	runner.save_replay_as = options.save_replay_as;
+	runner.random_seed = options.random_seed;
 	runner.run_game()?;
 	Ok(())
 }
@@ -490,11 +635,12 @@ fn get_unused_ports(n: usize) -> Vec<i32> {
 // Helpers
 fn set_static_data(bot: &mut Bot) -> SC2Result<()> {
 	let api = bot.api();
+	let reconnect = bot.reconnect;
 
 	debug!("Requesting GameInfo");
 	let mut req = Request::new();
 	req.mut_game_info();
-	let mut res = api.send(req)?;
+	let mut res = api.send_with_reconnect(req, reconnect)?;
 	let game_info = res.take_game_info().into_sc2();
 
 	debug!("Requesting GameData");
@@ -505,11 +651,18 @@ fn set_static_data(bot: &mut Bot) -> SC2Result<()> {
 	req_game_data.set_upgrade_id(true);
 	req_game_data.set_buff_id(true);
 	req_game_data.set_effect_id(true);
-	let mut res = api.send(req)?;
+	let mut res = api.send_with_reconnect(req, reconnect)?;
 	let game_data = Rs::new(res.take_data().into_sc2());
 
+	debug!("Requesting Ping");
+	let mut req = Request::new();
+	req.mut_ping();
+	let mut res = api.send_with_reconnect(req, reconnect)?;
+	let game_version = res.take_ping().take_game_version();
+
 	bot.game_info = game_info;
 	bot.game_data = game_data;
+	bot.game_version = game_version;
 
 	Ok(())
 }
@@ -585,9 +738,13 @@ fn wait_join(api: &API) -> SC2Result<u32> {
 
 	let res_join_game = res.get_join_game();
 	if res_join_game.has_error() {
-		let err = ProtoError::new(res_join_game.get_error(), res_join_game.get_error_details());
+		let err = format!(
+			"{:?}: {}",
+			res_join_game.get_error(),
+			res_join_game.get_error_details()
+		);
 		error!("{}", err);
-		Err(Box::new(err))
+		Err(SC2Error::InvalidResponse(err).into())
 	} else {
 		Ok(res_join_game.get_player_id())
 	}
@@ -599,8 +756,9 @@ where
 {
 	let mut req = Request::new();
 	req.mut_observation().set_disable_fog(true);
-	let res = bot.api().send(req)?;
+	let res = bot.api().send_with_reconnect(req, bot.reconnect)?;
 
+	bot.reset_for_new_game();
 	bot.init_data_for_unit();
 	let events = update_state(bot, res.get_observation())?;
 	bot.prepare_start();
@@ -619,31 +777,60 @@ where
 			actions.push(a.into_proto());
 		}
 		bot.clear_actions();
-		bot.api().send_request(req)?;
+		bot.api().send_with_reconnect(req, bot.reconnect)?;
 	}
 	if !realtime {
 		let mut req = Request::new();
 		req.mut_step().set_count(bot.game_step.get_locked());
-		bot.api().send_request(req)?;
+		bot.api().send_with_reconnect(req, bot.reconnect)?;
 	}
 	Ok(())
 }
 
-fn play_step<B>(bot: &mut B, iteration: usize, realtime: bool) -> SC2Result<bool>
+// Returns result of the game if it has ended, otherwise `None`
+fn play_step<B>(
+	bot: &mut B,
+	iteration: usize,
+	realtime: bool,
+	save_replay_as: Option<&str>,
+) -> SC2Result<Option<GameResult>>
 where
 	B: Player + DerefMut<Target = Bot> + Deref<Target = Bot>,
 {
+	// Bot requested to leave on previous step, leaving the game counts as defeat
+	if bot.leave_requested {
+		// Replay can't be saved after leaving the game
+		if let Some(path) = save_replay_as {
+			save_replay(bot.api(), path)?;
+		}
+		let mut req = Request::new();
+		req.mut_leave_game();
+		bot.api().send_request(req)?;
+		debug!("Bot left the game");
+		end_game(bot, GameResult::Defeat)?;
+		return Ok(Some(GameResult::Defeat));
+	}
+
 	let mut req = Request::new();
 	req.mut_observation().set_disable_fog(bot.disable_fog);
-	let res = bot.api().send(req)?;
+	let res = match bot.api().send_with_reconnect(req, bot.reconnect) {
+		Ok(res) => res,
+		// Connection with the game was lost, result is unknown
+		Err(e) => {
+			end_game(bot, GameResult::Undecided)?;
+			return Err(e.into());
+		}
+	};
 
 	if matches!(res.get_status(), Status::ended) {
 		let result = res.get_observation().get_player_result()[bot.player_id as usize - 1]
 			.get_result()
 			.into_sc2();
 		debug!("Result for bot: {:?}", result);
-		bot.on_end(result)?;
-		return Ok(false);
+		// Update state with the final observation, so end-game stats are available in `on_end`
+		update_state(bot, res.get_observation())?;
+		end_game(bot, result)?;
+		return Ok(Some(result));
 	}
 
 	let events = update_state(bot, res.get_observation())?;
@@ -652,7 +839,9 @@ where
 	for e in events {
 		bot.on_event(e)?;
 	}
+	let start = Instant::now();
 	bot.on_step(iteration)?;
+	bot.step_time = start.elapsed().as_secs_f32() * 1000.0;
 
 	let bot_actions = bot.get_actions();
 	if !bot_actions.is_empty() {
@@ -663,7 +852,7 @@ where
 			actions.push(a.into_proto());
 		}
 		bot.clear_actions();
-		bot.api().send_request(req)?;
+		bot.api().send_with_reconnect(req, bot.reconnect)?;
 		/*
 		let res = api.send(req);
 		let results = res.get_action().get_result();
@@ -681,14 +870,25 @@ where
 			debug_commands.push(cmd.into_proto())
 		}
 		bot.clear_debug_commands();
-		bot.api().send_request(req)?;
+		bot.api().send_with_reconnect(req, bot.reconnect)?;
 	}
 	if !realtime {
 		let mut req = Request::new();
 		req.mut_step().set_count(bot.game_step.get_locked());
-		bot.api().send_request(req)?;
+		bot.api().send_with_reconnect(req, bot.reconnect)?;
+	}
+	Ok(None)
+}
+
+fn end_game<B>(bot: &mut B, result: GameResult) -> SC2Result<()>
+where
+	B: Player + DerefMut<Target = Bot> + Deref<Target = Bot>,
+{
+	bot.on_end(result)?;
+	if bot.profiling {
+		info!("Profiling summary:\n{}", bot.profile_summary());
 	}
-	Ok(true)
+	Ok(())
 }
 
 fn save_replay(api: &API, path: &str) -> SC2Result<()> {
@@ -753,12 +953,12 @@ fn launch_client(sc2_path: &str, port: i32, sc2_version: Option<&str>) -> Child
 	process.spawn().expect("Can't launch SC2 process.")
 }
 
-fn connect_to_websocket(host: &str, port: i32) -> SC2Result<WS> {
+fn connect_to_websocket(host: &str, port: i32) -> SC2Result<API> {
 	let url = format!("ws://{}:{}/sc2api", host, port);
 	let (ws, _rs) = loop {
 		if let Ok(result) = connect(&url) {
 			break result;
 		}
 	};
-	Ok(ws)
+	Ok(API::new(ws, url))
 }
@@ -25,6 +25,34 @@ pub const ANTI_ARMOR_TARGET: BuffId = BuffId::RavenShredderMissileTint;
 /// Units disabled by raven's interference matrix have this buff.
 pub const INTERFERENCE_MATRIX_BUFF: BuffId = BuffId::RavenScramblerMissile;
 
+/// All types of mineral fields.
+pub const MINERAL_FIELD_IDS: [UnitTypeId; 15] = [
+	UnitTypeId::RichMineralField,
+	UnitTypeId::RichMineralField750,
+	UnitTypeId::MineralField,
+	UnitTypeId::MineralField450,
+	UnitTypeId::MineralField750,
+	UnitTypeId::LabMineralField,
+	UnitTypeId::LabMineralField750,
+	UnitTypeId::PurifierRichMineralField,
+	UnitTypeId::PurifierRichMineralField750,
+	UnitTypeId::PurifierMineralField,
+	UnitTypeId::PurifierMineralField750,
+	UnitTypeId::BattleStationMineralField,
+	UnitTypeId::BattleStationMineralField750,
+	UnitTypeId::MineralFieldOpaque,
+	UnitTypeId::MineralFieldOpaque900,
+];
+/// All types of vespene geysers.
+pub const VESPENE_GEYSER_IDS: [UnitTypeId; 6] = [
+	UnitTypeId::VespeneGeyser,
+	UnitTypeId::SpacePlatformGeyser,
+	UnitTypeId::RichVespeneGeyser,
+	UnitTypeId::ProtossVespeneGeyser,
+	UnitTypeId::PurifierVespeneGeyser,
+	UnitTypeId::ShakurasVespeneGeyser,
+];
+
 // #[cfg(windows)]
 pub(crate) const INHIBITOR_IDS: [UnitTypeId; 6] = [
 	UnitTypeId::InhibitorZoneSmall,
@@ -869,6 +897,81 @@ lazy_static! {
 		UnitTypeId::InhibitorZoneFlyingMedium => 5.0,
 		UnitTypeId::InhibitorZoneFlyingLarge => 6.0,
 	];
+	/// Placement sizes of buildings in tiles (side of square footprint) mapped to their ids.
+	pub static ref BUILDING_FOOTPRINTS: HashMap<UnitTypeId, u32> = hashmap![
+		// Terran
+		UnitTypeId::CommandCenter => 5,
+		UnitTypeId::CommandCenterFlying => 5,
+		UnitTypeId::OrbitalCommand => 5,
+		UnitTypeId::OrbitalCommandFlying => 5,
+		UnitTypeId::PlanetaryFortress => 5,
+		UnitTypeId::SupplyDepot => 2,
+		UnitTypeId::SupplyDepotLowered => 2,
+		UnitTypeId::Refinery => 3,
+		UnitTypeId::RefineryRich => 3,
+		UnitTypeId::Barracks => 3,
+		UnitTypeId::BarracksFlying => 3,
+		UnitTypeId::Factory => 3,
+		UnitTypeId::FactoryFlying => 3,
+		UnitTypeId::Starport => 3,
+		UnitTypeId::StarportFlying => 3,
+		UnitTypeId::EngineeringBay => 3,
+		UnitTypeId::Bunker => 3,
+		UnitTypeId::MissileTurret => 2,
+		UnitTypeId::SensorTower => 1,
+		UnitTypeId::GhostAcademy => 3,
+		UnitTypeId::Armory => 3,
+		UnitTypeId::FusionCore => 3,
+		UnitTypeId::TechLab => 2,
+		UnitTypeId::Reactor => 2,
+		UnitTypeId::BarracksTechLab => 2,
+		UnitTypeId::BarracksReactor => 2,
+		UnitTypeId::FactoryTechLab => 2,
+		UnitTypeId::FactoryReactor => 2,
+		UnitTypeId::StarportTechLab => 2,
+		UnitTypeId::StarportReactor => 2,
+		// Protoss
+		UnitTypeId::Nexus => 5,
+		UnitTypeId::Pylon => 2,
+		UnitTypeId::Assimilator => 3,
+		UnitTypeId::AssimilatorRich => 3,
+		UnitTypeId::Gateway => 3,
+		UnitTypeId::WarpGate => 3,
+		UnitTypeId::Forge => 3,
+		UnitTypeId::CyberneticsCore => 3,
+		UnitTypeId::PhotonCannon => 2,
+		UnitTypeId::ShieldBattery => 2,
+		UnitTypeId::TwilightCouncil => 3,
+		UnitTypeId::Stargate => 3,
+		UnitTypeId::RoboticsFacility => 3,
+		UnitTypeId::RoboticsBay => 3,
+		UnitTypeId::TemplarArchive => 3,
+		UnitTypeId::DarkShrine => 2,
+		UnitTypeId::FleetBeacon => 3,
+		// Zerg
+		UnitTypeId::Hatchery => 5,
+		UnitTypeId::Lair => 5,
+		UnitTypeId::Hive => 5,
+		UnitTypeId::Extractor => 3,
+		UnitTypeId::ExtractorRich => 3,
+		UnitTypeId::SpawningPool => 3,
+		UnitTypeId::EvolutionChamber => 3,
+		UnitTypeId::RoachWarren => 3,
+		UnitTypeId::BanelingNest => 3,
+		UnitTypeId::HydraliskDen => 3,
+		UnitTypeId::LurkerDenMP => 3,
+		UnitTypeId::InfestationPit => 3,
+		UnitTypeId::Spire => 3,
+		UnitTypeId::GreaterSpire => 3,
+		UnitTypeId::UltraliskCavern => 3,
+		UnitTypeId::NydusNetwork => 3,
+		UnitTypeId::NydusCanal => 3,
+		UnitTypeId::SpineCrawler => 2,
+		UnitTypeId::SporeCrawler => 2,
+		UnitTypeId::CreepTumor => 1,
+		UnitTypeId::CreepTumorBurrowed => 1,
+		UnitTypeId::CreepTumorQueen => 1,
+	];
 	pub(crate) static ref SPEED_BUFFS: HashMap<BuffId, f32> = hashmap![
 		BuffId::Stimpack => 1.5,
 		BuffId::StimpackMarauder => 1.5,
@@ -881,4 +984,99 @@ lazy_static! {
 		BuffId::InhibitorZoneFlyingTemporalField => 0.65,
 		BuffId::AccelerationZoneFlyingTemporalField => 1.35,
 	];
+	/// Energy costs of abilities mapped to their ids.
+	pub static ref ABILITY_ENERGY_COSTS: HashMap<AbilityId, u32> = hashmap![
+		// Terran
+		AbilityId::CalldownMULECalldownMULE => 50,
+		AbilityId::ScannerSweepScan => 50,
+		AbilityId::SupplyDropSupplyDrop => 50,
+		AbilityId::EMPEMP => 75,
+		AbilityId::EffectGhostSnipe => 50,
+		AbilityId::BehaviorCloakOnGhost => 25,
+		AbilityId::BehaviorCloakOnBanshee => 25,
+		AbilityId::BuildAutoTurretAutoTurret => 50,
+		AbilityId::EffectInterferenceMatrix => 50,
+		AbilityId::EffectAntiArmorMissile => 75,
+		// Protoss
+		AbilityId::EffectChronoBoostEnergyCost => 50,
+		AbilityId::PsiStormPsiStorm => 75,
+		AbilityId::FeedbackFeedback => 50,
+		AbilityId::GuardianShieldGuardianShield => 75,
+		AbilityId::ForceFieldForceField => 50,
+		AbilityId::OracleRevelationOracleRevelation => 25,
+		AbilityId::BuildStasisTrap => 50,
+		AbilityId::BehaviorPulsarBeamOn => 25,
+		AbilityId::EffectTimeWarp => 50,
+		// Zerg
+		AbilityId::EffectInjectLarva => 25,
+		AbilityId::BuildCreepTumorQueen => 25,
+		AbilityId::TransfusionTransfusion => 50,
+		AbilityId::FungalGrowthFungalGrowth => 75,
+		AbilityId::NeuralParasiteNeuralParasite => 100,
+		AbilityId::EffectAbduct => 75,
+		AbilityId::BlindingCloudBlindingCloud => 100,
+		AbilityId::ParasiticBombParasiticBomb => 125,
+		AbilityId::ContaminateContaminate => 125,
+	];
+	/// Upgrades required to use abilities mapped to ids of these abilities.
+	pub static ref ABILITY_UPGRADES: HashMap<AbilityId, UpgradeId> = hashmap![
+		AbilityId::EffectStim => UpgradeId::Stimpack,
+		AbilityId::EffectStimMarine => UpgradeId::Stimpack,
+		AbilityId::EffectStimMarauder => UpgradeId::Stimpack,
+		AbilityId::BehaviorCloakOnGhost => UpgradeId::PersonalCloaking,
+		AbilityId::BehaviorCloakOnBanshee => UpgradeId::BansheeCloak,
+		AbilityId::EffectBlinkStalker => UpgradeId::BlinkTech,
+		AbilityId::EffectShadowStride => UpgradeId::DarkTemplarBlinkUpgrade,
+		AbilityId::PsiStormPsiStorm => UpgradeId::PsiStormTech,
+		AbilityId::NeuralParasiteNeuralParasite => UpgradeId::NeuralParasite,
+		AbilityId::BurrowDownDrone => UpgradeId::Burrow,
+		AbilityId::BurrowDownZergling => UpgradeId::Burrow,
+		AbilityId::BurrowDownBaneling => UpgradeId::Burrow,
+		AbilityId::BurrowDownRoach => UpgradeId::Burrow,
+		AbilityId::BurrowDownHydralisk => UpgradeId::Burrow,
+		AbilityId::BurrowDownQueen => UpgradeId::Burrow,
+		AbilityId::BurrowDownInfestorTerran => UpgradeId::Burrow,
+	];
+	/// Radiuses of effects mapped to their ids.
+	pub static ref EFFECT_RADIUS: HashMap<EffectId, f32> = hashmap![
+		EffectId::PsiStormPersistent => 1.5,
+		EffectId::GuardianShieldPersistent => 4.5,
+		EffectId::TemporalFieldGrowingBubbleCreatePersistent => 2.0,
+		EffectId::TemporalFieldAfterBubbleCreatePersistent => 2.0,
+		EffectId::ThermalLancesForward => 0.3,
+		EffectId::ScannerSweep => 13.0,
+		EffectId::NukePersistent => 8.0,
+		EffectId::LiberatorTargetMorphDelayPersistent => 5.0,
+		EffectId::LiberatorTargetMorphPersistent => 5.0,
+		EffectId::BlindingCloudCP => 2.0,
+		EffectId::RavagerCorrosiveBileCP => 0.5,
+		EffectId::LurkerMP => 0.5,
+	];
+	/// Effects dealing damage to units inside of them mapped to `true`
+	/// if they also damage units of effect's owner.
+	pub static ref DAMAGING_EFFECTS: HashMap<EffectId, bool> = hashmap![
+		EffectId::PsiStormPersistent => true,
+		EffectId::ThermalLancesForward => false,
+		EffectId::NukePersistent => true,
+		EffectId::LiberatorTargetMorphDelayPersistent => false,
+		EffectId::LiberatorTargetMorphPersistent => false,
+		EffectId::RavagerCorrosiveBileCP => true,
+		EffectId::LurkerMP => false,
+	];
+	/// Cooldowns of unit abilities in game loops (frames) mapped to their ids.
+	///
+	/// Only abilities with fixed cooldown are here, energy-based spells without cooldown aren't included.
+	pub static ref ABILITY_COOLDOWNS: HashMap<AbilityId, u32> = hashmap![
+		AbilityId::EffectBlink => 157,
+		AbilityId::EffectBlinkStalker => 157,
+		AbilityId::EffectShadowStride => 314,
+		AbilityId::AdeptPhaseShiftAdeptPhaseShift => 246,
+		AbilityId::EffectPurificationNova => 320,
+		AbilityId::EffectVoidRayPrismaticAlignment => 963,
+		AbilityId::EffectMassRecallNexus => 2912,
+		AbilityId::EffectCorrosiveBile => 157,
+		AbilityId::EffectSpawnLocusts => 963,
+		AbilityId::EffectTacticalJump => 1590,
+		AbilityId::KD8ChargeKD8Charge => 314,
+	];
 }
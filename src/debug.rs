@@ -3,6 +3,8 @@
 use crate::{
 	geometry::{Point2, Point3},
 	ids::UnitTypeId,
+	pixel_map::{z_height, ByteMap},
+	unit::Unit,
 	IntoProto,
 };
 use num_traits::ToPrimitive;
@@ -18,19 +20,33 @@ type ScreenPos = (f32, f32);
 /// Helper struct for interacting with Debug API.
 /// Can be accessed through [`debug`] field of bot.
 ///
+/// All drawings made during a step are sent in a single draw request at the end of that step
+/// and disappear on the next step, so they should be redrawn every step if needed.
+///
 /// [`debug`]: crate::bot::Bot::debug
 #[derive(Default)]
 pub struct Debugger {
 	debug_commands: Vec<DebugCommand>,
 	debug_drawings: Vec<DebugDraw>,
 	kill_tags: FxHashSet<u64>,
+	has_drawings: bool,
+	terrain_boxes: Vec<(Point2, Point2, Option<Color>)>,
 }
 impl Debugger {
-	pub(crate) fn get_commands(&mut self) -> &[DebugCommand] {
+	pub(crate) fn get_commands(&mut self, terrain_height: &ByteMap) -> &[DebugCommand] {
+		for (p0, p1, color) in std::mem::take(&mut self.terrain_boxes) {
+			let z = z_height(terrain_height, p0.into()).max(z_height(terrain_height, p1.into()));
+			self.draw_box(p0.to3(z), p1.to3(z + 1.0), color);
+		}
 		let commands = &mut self.debug_commands;
 
 		if !self.debug_drawings.is_empty() {
 			commands.push(DebugCommand::Draw(self.debug_drawings.drain(..).collect()));
+			self.has_drawings = true;
+		} else if self.has_drawings {
+			// Empty draw request clears drawings left from previous step
+			commands.push(DebugCommand::Draw(Vec::new()));
+			self.has_drawings = false;
 		}
 		if !self.kill_tags.is_empty() {
 			commands.push(DebugCommand::KillUnit(self.kill_tags.drain().collect()));
@@ -78,6 +94,20 @@ impl Debugger {
 	pub fn draw_sphere(&mut self, pos: Point3, radius: f32, color: Option<Color>) {
 		self.debug_drawings.push(DebugDraw::Sphere(pos, radius, color));
 	}
+	/// Alias for [`draw_line`](Self::draw_line).
+	pub fn debug_line(&mut self, from: Point3, to: Point3, color: Option<Color>) {
+		self.draw_line(from, to, color);
+	}
+	/// Draws box in game world with 2d corners `p0` and `p1`, placed on terrain level.
+	pub fn debug_box2_on(&mut self, p0: Point2, p1: Point2, color: Option<Color>) {
+		self.terrain_boxes.push((p0, p1, color));
+	}
+	/// Draws sphere with given radius around the unit.
+	///
+	/// Useful to visualize attack ranges, e.g. `debug_sphere_on_unit(u, u.real_ground_range(), None)`.
+	pub fn debug_sphere_on_unit(&mut self, unit: &Unit, radius: f32, color: Option<Color>) {
+		self.draw_sphere(unit.position3d(), radius, color);
+	}
 	/// Spawns units using given commands in format: (unit type, owner's player id, position, count).
 	pub fn create_units<'a, T>(&mut self, cmds: T)
 	where
@@ -98,11 +128,14 @@ impl Debugger {
 	where
 		T: IntoIterator<Item = &'a (u64, UnitValue, u32)>,
 	{
-		self.debug_commands.extend(
-			cmds.into_iter()
-				.copied()
-				.map(|(tag, unit_value, value)| DebugCommand::SetUnitValue(tag, unit_value, value)),
-		);
+		self.debug_commands.extend(cmds.into_iter().copied().map(|(tag, unit_value, value)| {
+			DebugCommand::SetUnitValue(tag, unit_value, value as f32)
+		}));
+	}
+	/// Sets value of given type for unit with given tag.
+	pub fn set_unit_value(&mut self, tag: u64, unit_value: UnitValue, value: f32) {
+		self.debug_commands
+			.push(DebugCommand::SetUnitValue(tag, unit_value, value));
 	}
 	/// Ends game with Victory for bot
 	pub fn win_game(&mut self) {
@@ -189,7 +222,7 @@ pub(crate) enum DebugCommand {
 	// TestProcess,
 	// SetScore,
 	EndGame(bool),
-	SetUnitValue(u64, UnitValue, u32),
+	SetUnitValue(u64, UnitValue, f32),
 }
 impl IntoProto<ProtoDebugCommand> for &DebugCommand {
 	fn into_proto(self) -> ProtoDebugCommand {
@@ -217,7 +250,7 @@ impl IntoProto<ProtoDebugCommand> for &DebugCommand {
 				let cmd = proto.mut_unit_value();
 				cmd.set_unit_tag(*tag);
 				cmd.set_unit_value(unit_value.into_proto());
-				cmd.set_value(*value as f32);
+				cmd.set_value(*value);
 			}
 		}
 		proto
@@ -308,7 +341,8 @@ pub(crate) enum DebugDraw {
 	Sphere(Point3, f32, Option<Color>),
 }
 
-/// Value type used in [`set_unit_values`](Debugger::set_unit_values) commands.
+/// Value type used in [`set_unit_values`](Debugger::set_unit_values)
+/// and [`set_unit_value`](Debugger::set_unit_value) commands.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Copy)]
 pub enum UnitValue {
@@ -35,6 +35,24 @@ pub struct GameData {
 	/// Information about effects mapped to `EffectId`s.
 	pub effects: FxHashMap<EffectId, EffectData>,
 }
+impl GameData {
+	/// Returns cost of given unit type or default (zero) cost if there's no data for it.
+	///
+	/// Note: cost of units and structures made by morphing is cumulative here
+	/// (e.g. Baneling costs as Zergling + morph, Lair as Hatchery + morph).
+	/// Use [`get_unit_cost`](crate::bot::Bot::get_unit_cost) to get cost of morph only.
+	pub fn cost(&self, unit: UnitTypeId) -> Cost {
+		self.units
+			.get(&unit)
+			.map_or_else(Cost::default, |data| data.cost())
+	}
+	/// Returns cost of given upgrade or default (zero) cost if there's no data for it.
+	pub fn upgrade_cost(&self, upgrade: UpgradeId) -> Cost {
+		self.upgrades
+			.get(&upgrade)
+			.map_or_else(Cost::default, |data| data.cost())
+	}
+}
 impl FromProto<ResponseData> for GameData {
 	fn from_proto(data: ResponseData) -> Self {
 		Self {
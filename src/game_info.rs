@@ -35,11 +35,25 @@ pub struct GameInfo {
 	/// Usually maps have some unplayable area around it, where units can't exist.
 	/// This rectangle is only playble area on that map.
 	pub playable_area: Rect,
-	/// All starting locations of opponents.
+	/// All possible starting locations of opponents (bot's own start location isn't included).
+	///
+	/// Bot's start location is [`Bot::start_location`](crate::bot::Bot::start_location).
 	pub start_locations: Vec<Point2>,
-	/// Center of the map.
+	/// Center of the playable area of the map.
 	pub map_center: Point2,
 }
+impl GameInfo {
+	/// Grid with information about pathable tiles,
+	/// check it with [`walkable`](crate::pixel_map::PixelMapExt::walkable).
+	pub fn pathing_grid(&self) -> &PixelMap {
+		&self.pathing_grid
+	}
+	/// Grid with information about buildable tiles,
+	/// check it with [`buildable`](crate::pixel_map::PixelMapExt::buildable).
+	pub fn placement_grid(&self) -> &PixelMap {
+		&self.placement_grid
+	}
+}
 impl FromProto<ResponseGameInfo> for GameInfo {
 	fn from_proto(game_info: ResponseGameInfo) -> Self {
 		let start_raw = game_info.get_start_raw();
@@ -102,6 +102,11 @@ impl Point2 {
 	pub fn len(self) -> f32 {
 		self.len_squared().sqrt()
 	}
+	/// Returns length of the vector, alias for [`len`](Self::len).
+	#[inline]
+	pub fn length(self) -> f32 {
+		self.len()
+	}
 	/// Normalizes the vector.
 	pub fn normalize(self) -> Self {
 		self / self.len()
@@ -470,6 +475,27 @@ impl Point3 {
 	pub fn to2(self) -> Point2 {
 		Point2 { x: self.x, y: self.y }
 	}
+	/// Returns squared length of the vector.
+	pub fn len_squared(self) -> f32 {
+		self.x.powi(2) + self.y.powi(2) + self.z.powi(2)
+	}
+	/// Returns length of the vector.
+	pub fn len(self) -> f32 {
+		self.len_squared().sqrt()
+	}
+	/// Returns length of the vector, alias for [`len`](Self::len).
+	#[inline]
+	pub fn length(self) -> f32 {
+		self.len()
+	}
+	/// Normalizes the vector.
+	pub fn normalize(self) -> Self {
+		self / self.len()
+	}
+	/// Returns dot product of the vectors.
+	pub fn dot(self, other: Self) -> f32 {
+		self.x * other.x + self.y * other.y + self.z * other.z
+	}
 }
 
 impl From<Point3> for Point2 {
@@ -580,6 +606,45 @@ impl Div<f32> for Point3 {
 		}
 	}
 }
+impl AddAssign for Point3 {
+	fn add_assign(&mut self, other: Self) {
+		self.x += other.x;
+		self.y += other.y;
+		self.z += other.z;
+	}
+}
+impl SubAssign for Point3 {
+	fn sub_assign(&mut self, other: Self) {
+		self.x -= other.x;
+		self.y -= other.y;
+		self.z -= other.z;
+	}
+}
+impl MulAssign<f32> for Point3 {
+	fn mul_assign(&mut self, other: f32) {
+		self.x *= other;
+		self.y *= other;
+		self.z *= other;
+	}
+}
+impl DivAssign<f32> for Point3 {
+	fn div_assign(&mut self, other: f32) {
+		self.x /= other;
+		self.y /= other;
+		self.z /= other;
+	}
+}
+impl Neg for Point3 {
+	type Output = Self;
+
+	fn neg(self) -> Self {
+		Self {
+			x: -self.x,
+			y: -self.y,
+			z: -self.z,
+		}
+	}
+}
 impl Sum for Point3 {
 	fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
 		iter.fold(Default::default(), Add::add)
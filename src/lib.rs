@@ -104,7 +104,7 @@ You always can do the same thing by hands if needed.
 ### Common opponent's information
 | Field                     | Type       | Description                                              |
 |---------------------------|------------|----------------------------------------------------------|
-| `self.enemy_race`         | [`Race`]   | Requested race of your opponent.                         |
+| `self.enemy_race`         | [`Race`]   | Requested race of your opponent (actual once detected).  |
 | `self.enemy_player_id`    | `u32`      | Opponent in-game id (usually `1` or `2` in 1v1 matches). |
 | `self.opponent_id`        | `String`   | Opponent id on ladder, filled in `--OpponentId`.         |
 | `self.enemy_start`        | [`Point2`] | Opponent's starting location.                            |
@@ -344,16 +344,17 @@ pub mod prelude {
 	pub use crate::units::rayon::ParUnitsIterator;
 	pub use crate::{
 		action::Target,
-		bot::PlacementOptions,
+		bot::{PlacementOptions, RushOptions},
 		client::{
-			run_ladder_game, run_vs_computer, run_vs_human, LaunchOptions, RunnerMulti, RunnerSingle,
-			SC2Result,
+			run_ladder_game, run_many, run_vs_computer, run_vs_human, ComputerGameConfig, LaunchOptions,
+			RunnerMulti, RunnerSingle, SC2Result,
 		},
 		consts::{ALL_PRODUCERS, PRODUCERS, RESEARCHERS, TECH_REQUIREMENTS},
 		distance::{Center, Distance, DistanceIterator, DistanceSlice},
 		game_state::Alliance,
 		geometry::Point2,
 		ids::*,
+		pixel_map::PixelMapExt,
 		player::{AIBuild, Computer, Difficulty, GameResult, Race},
 		unit::Unit,
 		units::{iter::UnitsIterator, Units},
@@ -368,6 +369,7 @@ mod paths;
 pub mod action;
 pub mod api;
 pub mod bot;
+pub mod build_order;
 pub mod client;
 pub mod consts;
 pub mod debug;
@@ -380,7 +382,10 @@ pub mod ids;
 pub mod pixel_map;
 pub mod player;
 pub mod ramp;
+pub mod roles;
 pub mod score;
+pub mod snapshot;
+pub mod spatial;
 pub mod unit;
 pub mod units;
 pub mod utils;
@@ -702,6 +707,14 @@ pub trait Player {
 	/// Returns settings used to connect bot to the game.
 	fn get_player_settings(&self) -> PlayerSettings;
 	/// Called once on first step (i.e on game start).
+	///
+	/// It's called after the first observation is received and static map data
+	/// (expansions, ramps, start locations) is precomputed, and after [`on_event`](Self::on_event)
+	/// got events of the first step. This is the place for any expensive precomputation
+	/// and caching, which otherwise would need a "first step" check in [`on_step`](Self::on_step).
+	///
+	/// Always called before the first [`on_step`](Self::on_step) and [`on_end`](Self::on_end).
+	/// Actions issued here are sent to the game the same way as in `on_step`.
 	fn on_start(&mut self) -> SC2Result<()> {
 		Ok(())
 	}
@@ -710,7 +723,14 @@ pub trait Player {
 		Ok(())
 	}
 	/// Called once on last step with a result for your bot.
-	fn on_end(&self, _result: GameResult) -> SC2Result<()> {
+	///
+	/// Before the call bot's [`state`](crate::bot::Bot::state) is updated with the final observation,
+	/// so end-game stats (e.g. score) are available here.
+	/// It's also called with [`GameResult::Undecided`] if connection with the game was lost
+	/// and with [`GameResult::Defeat`] if bot [left the game](crate::bot::Bot::leave_game).
+	///
+	/// This is the place to persist learning data or log the outcome.
+	fn on_end(&mut self, _result: GameResult) -> SC2Result<()> {
 		Ok(())
 	}
 	/// Called when different events happen.
@@ -1,7 +1,10 @@
 //! Data structures, used to store map data.
 #![allow(missing_docs)]
 
-use crate::{geometry::Point2, FromProto};
+use crate::{
+	geometry::{Point2, Rect},
+	FromProto,
+};
 use ndarray::Array2;
 use num_traits::FromPrimitive;
 use sc2_proto::common::ImageData;
@@ -17,6 +20,13 @@ pub type ByteMap = Array2<u8>;
 /// 2-Dimensional Array that represents visibility.
 pub type VisibilityMap = Array2<Visibility>;
 
+/// Returns actual height in 3D space of terrain on given position of height map.
+pub(crate) fn z_height(terrain_height: &ByteMap, pos: (usize, usize)) -> f32 {
+	terrain_height
+		.get(pos)
+		.map_or(0.0, |h| *h as f32 * 32.0 / 255.0 - 16.0)
+}
+
 impl<T> Index<Point2> for Array2<T> {
 	type Output = T;
 
@@ -32,6 +42,43 @@ impl<T> IndexMut<Point2> for Array2<T> {
 	}
 }
 
+/// Convenient checks of [`PixelMap`] tiles by world position.
+///
+/// Map grids are indexed as `[x][y]`, same as world positions (`y` axis isn't inverted).
+/// In [`pathing_grid`] and [`placement_grid`] [`Pixel::Empty`] means tile is free
+/// (i.e. walkable or buildable) and [`Pixel::Set`] means tile is blocked.
+///
+/// [`pathing_grid`]: crate::game_info::GameInfo::pathing_grid
+/// [`placement_grid`]: crate::game_info::GameInfo::placement_grid
+pub trait PixelMapExt {
+	/// Checks if pixel on given position is [`Set`](Pixel::Set) (i.e. tile is blocked).
+	/// Positions outside of the map are considered set.
+	fn is_set(&self, pos: Point2) -> bool;
+	/// Checks if ground units can walk through given position, use on [`pathing_grid`].
+	///
+	/// [`pathing_grid`]: crate::game_info::GameInfo::pathing_grid
+	fn walkable(&self, pos: Point2) -> bool {
+		!self.is_set(pos)
+	}
+	/// Checks if buildings can be placed on given position, use on [`placement_grid`].
+	///
+	/// [`placement_grid`]: crate::game_info::GameInfo::placement_grid
+	fn buildable(&self, pos: Point2) -> bool {
+		!self.is_set(pos)
+	}
+}
+impl PixelMapExt for PixelMap {
+	fn is_set(&self, pos: Point2) -> bool {
+		if pos.x < 0.0 || pos.y < 0.0 {
+			return true;
+		}
+		match self.get(<(usize, usize)>::from(pos)) {
+			Some(pixel) => pixel.is_set(),
+			None => true,
+		}
+	}
+}
+
 fn to_binary(n: u8) -> impl Iterator<Item = Pixel> {
 	(0..8).rev().map(move |x| Pixel::from_u8((n >> x) & 1).unwrap())
 }
@@ -123,3 +170,138 @@ impl Default for Visibility {
 		Visibility::Hidden
 	}
 }
+
+/// Typed 2-Dimensional grid stored in flat row-major `Vec`.
+///
+/// Useful for custom per-tile data like influence or cost maps.
+/// Grid can be bound to some area of the map (e.g. [`playable_area`]),
+/// then world coordinates are converted to tile indices with [`get_world`](Self::get_world).
+///
+/// [`playable_area`]: crate::game_info::GameInfo::playable_area
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+	data: Vec<T>,
+	width: usize,
+	height: usize,
+	x0: usize,
+	y0: usize,
+}
+impl<T: Default + Clone> Grid<T> {
+	/// Constructs new grid with given size filled with default values.
+	pub fn new(width: usize, height: usize) -> Self {
+		Self::filled(width, height, T::default())
+	}
+	/// Constructs new grid covering given area of the map, filled with default values.
+	pub fn from_area(area: Rect) -> Self {
+		let mut grid = Self::new(area.x1.saturating_sub(area.x0), area.y1.saturating_sub(area.y0));
+		grid.x0 = area.x0;
+		grid.y0 = area.y0;
+		grid
+	}
+}
+impl<T: Clone> Grid<T> {
+	/// Constructs new grid with given size filled with given value.
+	pub fn filled(width: usize, height: usize, value: T) -> Self {
+		Self {
+			data: vec![value; width * height],
+			width,
+			height,
+			x0: 0,
+			y0: 0,
+		}
+	}
+	/// Sets all tiles of grid to given value.
+	pub fn fill(&mut self, value: T) {
+		for x in &mut self.data {
+			*x = value.clone();
+		}
+	}
+}
+impl<T> Grid<T> {
+	/// Width of the grid in tiles.
+	pub fn width(&self) -> usize {
+		self.width
+	}
+	/// Height of the grid in tiles.
+	pub fn height(&self) -> usize {
+		self.height
+	}
+	fn tile_index(&self, pos: Point2) -> Option<usize> {
+		if pos.x < 0.0 || pos.y < 0.0 {
+			return None;
+		}
+		let (x, y) = <(usize, usize)>::from(pos);
+		if x < self.width && y < self.height {
+			Some(y * self.width + x)
+		} else {
+			None
+		}
+	}
+	/// Converts world position to tile position of the grid.
+	/// Returns `None` if position is out of grid bounds.
+	///
+	/// Inverse of [`tile_to_world`](Self::tile_to_world).
+	pub fn world_to_tile(&self, pos: Point2) -> Option<(usize, usize)> {
+		let tile = Point2::new(pos.x - self.x0 as f32, pos.y - self.y0 as f32);
+		self.tile_index(tile).map(|_| tile.into())
+	}
+	/// Converts tile position of the grid to the world position of its center.
+	///
+	/// Inverse of [`world_to_tile`](Self::world_to_tile).
+	pub fn tile_to_world(&self, (x, y): (usize, usize)) -> Point2 {
+		Point2::from((x + self.x0, y + self.y0))
+	}
+	/// Returns value of the tile at given position or `None` if it's out of bounds.
+	pub fn get(&self, pos: Point2) -> Option<&T> {
+		self.tile_index(pos).map(|i| &self.data[i])
+	}
+	/// Returns mutable reference to the tile at given position or `None` if it's out of bounds.
+	pub fn get_mut(&mut self, pos: Point2) -> Option<&mut T> {
+		self.tile_index(pos).map(move |i| &mut self.data[i])
+	}
+	/// Sets value of the tile at given position and returns previous one.
+	/// Returns `None` if position is out of bounds.
+	pub fn set(&mut self, pos: Point2, value: T) -> Option<T> {
+		self.get_mut(pos).map(|x| std::mem::replace(x, value))
+	}
+	/// Returns value of the tile at given world position or `None` if it's out of bounds.
+	pub fn get_world(&self, pos: Point2) -> Option<&T> {
+		self.world_to_tile(pos).map(|tile| &self[tile])
+	}
+	/// Returns mutable reference to the tile at given world position or `None` if it's out of bounds.
+	pub fn get_world_mut(&mut self, pos: Point2) -> Option<&mut T> {
+		self.world_to_tile(pos).map(move |tile| &mut self[tile])
+	}
+	/// Iterates over values of all tiles in row-major order.
+	pub fn iter(&self) -> std::slice::Iter<'_, T> {
+		self.data.iter()
+	}
+	/// Mutably iterates over values of all tiles in row-major order.
+	pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+		self.data.iter_mut()
+	}
+	/// Iterates over tile positions `(x, y)` and their values in row-major order.
+	pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+		let width = self.width;
+		self.data
+			.iter()
+			.enumerate()
+			.map(move |(i, x)| ((i % width, i / width), x))
+	}
+}
+impl<T> Index<(usize, usize)> for Grid<T> {
+	type Output = T;
+
+	#[inline]
+	fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+		assert!(x < self.width && y < self.height, "Grid index out of bounds");
+		&self.data[y * self.width + x]
+	}
+}
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+	#[inline]
+	fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+		assert!(x < self.width && y < self.height, "Grid index out of bounds");
+		&mut self.data[y * self.width + x]
+	}
+}
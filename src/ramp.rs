@@ -17,6 +17,8 @@ pub struct Ramps {
 	pub my: Ramp,
 	/// Ramp to opponent's main base.
 	pub enemy: Ramp,
+	/// Ramp closest to your natural expansion.
+	pub natural: Ramp,
 }
 
 type Pos = (usize, usize);
@@ -77,6 +79,8 @@ impl Ramp {
 		result
 	}
 	/// Returns center of upper points of the ramp.
+	///
+	/// Returned position can be converted to [`Point2`] with `Point2::from(pos)`.
 	pub fn top_center(&self) -> Option<Pos> {
 		let ps = self.upper();
 		if ps.is_empty() {
@@ -88,6 +92,8 @@ impl Ramp {
 		}
 	}
 	/// Returns center of lower points of the ramp.
+	///
+	/// Returned position can be converted to [`Point2`] with `Point2::from(pos)`.
 	pub fn bottom_center(&self) -> Option<Pos> {
 		let ps = self.lower();
 		if ps.is_empty() {
@@ -97,6 +103,10 @@ impl Ramp {
 			Some((x / ps.len(), y / ps.len()))
 		}
 	}
+	/// Returns center of all points of the ramp.
+	pub fn center(&self) -> Option<Point2> {
+		self.points.iter().map(|p| Point2::from(*p)).center()
+	}
 	fn upper2_for_ramp_wall(&self) -> Option<[Pos; 2]> {
 		let mut upper = self.upper();
 		if upper.len() > 5 {
@@ -116,6 +126,8 @@ impl Ramp {
 		}
 	}
 	/// Returns correct positions to build corner supplies in terran wall.
+	///
+	/// Returns `None` for ramps which aren't standard main base ramps (i.e. have unusual shape).
 	pub fn corner_depots(&self) -> Option<[Point2; 2]> {
 		if let Some(ps) = self.upper2_for_ramp_wall() {
 			let p1 = Point2::from(ps[0]);
@@ -144,6 +156,11 @@ impl Ramp {
 		}
 		None
 	}
+	/// Alias for [`barracks_in_middle`](Self::barracks_in_middle).
+	#[inline]
+	pub fn barracks_middle(&self) -> Option<Point2> {
+		self.barracks_in_middle()
+	}
 	/// Returns correct position to build barrack in terran wall with addon.
 	pub fn barracks_correct_placement(&self) -> Option<Point2> {
 		self.barracks_in_middle().map(|pos| {
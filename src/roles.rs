@@ -0,0 +1,86 @@
+//! Storage for roles of units (e.g. scout, defender, builder),
+//! which is kept between steps.
+
+use crate::units::Units;
+use rustc_hash::FxHashMap;
+
+/// Roles of units mapped to their tags.
+///
+/// Generic over type of role, so bot can define it's own, for example:
+/// ```
+/// use rust_sc2::roles::Roles;
+///
+/// #[derive(PartialEq)]
+/// enum Role {
+///     Scout,
+///     Defender,
+/// }
+///
+/// let mut roles = Roles::new();
+/// roles.assign(1, Role::Scout);
+/// roles.assign(2, Role::Defender);
+/// assert!(roles.role_of(1) == Some(&Role::Scout));
+/// assert_eq!(roles.tags_with(&Role::Defender).collect::<Vec<_>>(), vec![2]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Roles<R> {
+	roles: FxHashMap<u64, R>,
+}
+impl<R> Default for Roles<R> {
+	fn default() -> Self {
+		Self {
+			roles: Default::default(),
+		}
+	}
+}
+impl<R> Roles<R> {
+	/// Constructs new empty storage of roles.
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Assigns role to unit with given tag and returns it's previous role.
+	pub fn assign(&mut self, tag: u64, role: R) -> Option<R> {
+		self.roles.insert(tag, role)
+	}
+	/// Removes role of unit with given tag and returns it.
+	pub fn unassign(&mut self, tag: u64) -> Option<R> {
+		self.roles.remove(&tag)
+	}
+	/// Returns role of unit with given tag or `None` if it has no role.
+	pub fn role_of(&self, tag: u64) -> Option<&R> {
+		self.roles.get(&tag)
+	}
+	/// Removes roles of units which aren't present in given collection (i.e. dead ones).
+	pub fn clear_dead(&mut self, units: &Units) {
+		self.roles.retain(|tag, _| units.contains_tag(*tag));
+	}
+	/// Removes all roles.
+	pub fn clear(&mut self) {
+		self.roles.clear();
+	}
+	/// Returns number of units with roles.
+	pub fn len(&self) -> usize {
+		self.roles.len()
+	}
+	/// Returns `true` if no units have roles.
+	pub fn is_empty(&self) -> bool {
+		self.roles.is_empty()
+	}
+	/// Returns an iterator over pairs of unit tags and their roles.
+	pub fn iter(&self) -> impl Iterator<Item = (u64, &R)> {
+		self.roles.iter().map(|(tag, role)| (*tag, role))
+	}
+}
+impl<R: PartialEq> Roles<R> {
+	/// Returns an iterator over tags of units with given role.
+	pub fn tags_with<'a>(&'a self, role: &'a R) -> impl Iterator<Item = u64> + 'a {
+		self.roles
+			.iter()
+			.filter(move |(_, r)| *r == role)
+			.map(|(tag, _)| *tag)
+	}
+	/// Makes new collection of units from given ones, which have given role.
+	pub fn units_with(&self, role: &R, units: &Units) -> Units {
+		units.find_tags(self.roles.iter().filter(|(_, r)| *r == role).map(|(tag, _)| tag))
+	}
+}
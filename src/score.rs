@@ -23,6 +23,7 @@ impl Default for ScoreType {
 	}
 }
 
+/// Score value split by categories of units and spendings.
 #[derive(Default, Clone)]
 pub struct Category {
 	pub none: f32,
@@ -43,6 +44,7 @@ impl FromProto<&CategoryScoreDetails> for Category {
 	}
 }
 
+/// Score value split by unit vitals.
 #[derive(Default, Clone)]
 pub struct Vital {
 	pub life: f32,
@@ -61,7 +63,8 @@ impl FromProto<&VitalScoreDetails> for Vital {
 
 /// All kinds of scores stored here.
 ///
-/// Can be accessed through [state.observation.score](crate::game_state::Observation::score).
+/// Can be accessed through [state.observation.score](crate::game_state::Observation::score)
+/// or [`Bot::score`](crate::bot::Bot::score).
 #[derive(Default, Clone)]
 pub struct Score {
 	pub score_type: ScoreType,
@@ -71,18 +74,26 @@ pub struct Score {
 	pub idle_worker_time: f32,
 	pub total_value_units: f32,
 	pub total_value_structures: f32,
+	/// Total value (minerals + vespene) of killed enemy units.
 	pub killed_value_units: f32,
+	/// Total value (minerals + vespene) of killed enemy structures.
 	pub killed_value_structures: f32,
+	/// Minerals collected during the game.
 	pub collected_minerals: f32,
+	/// Vespene collected during the game.
 	pub collected_vespene: f32,
+	/// Current rate of minerals collection per minute.
 	pub collection_rate_minerals: f32,
+	/// Current rate of vespene collection per minute.
 	pub collection_rate_vespene: f32,
 	pub spent_minerals: f32,
 	pub spent_vespene: f32,
 	pub food_used: Category,
 	pub killed_minerals: Category,
 	pub killed_vespene: Category,
+	/// Minerals lost in destroyed units and structures.
 	pub lost_minerals: Category,
+	/// Vespene lost in destroyed units and structures.
 	pub lost_vespene: Category,
 	pub friendly_fire_minerals: Category,
 	pub friendly_fire_vespene: Category,
@@ -90,7 +101,9 @@ pub struct Score {
 	pub used_vespene: Category,
 	pub total_used_minerals: Category,
 	pub total_used_vespene: Category,
+	/// Total damage dealt to enemies.
 	pub total_damage_dealt: Vital,
+	/// Total damage taken from enemies.
 	pub total_damage_taken: Vital,
 	pub total_healed: Vital,
 	pub current_apm: f32,
@@ -0,0 +1,118 @@
+//! Lightweight summaries of game state, which can be serialized (with `serde` feature)
+//! and dumped to disk or sent over network, e.g. for machine learning pipelines.
+//!
+//! Use [`Bot::snapshot`] or [`Bot::snapshot_with_units`] to create them.
+
+use crate::{
+	bot::{Bot, Locked},
+	ids::{UnitTypeId, UpgradeId},
+	unit::Unit,
+};
+use rustc_hash::FxHashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Current version of [`GameSnapshot`] format.
+///
+/// Incremented every time fields of snapshot change,
+/// so downstream parsers can tell different formats apart.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Summary of game state on some step.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct GameSnapshot {
+	/// Version of snapshot format, equals to [`SNAPSHOT_VERSION`] at the moment of creation.
+	pub version: u32,
+	/// Current game tick (frame).
+	pub game_loop: u32,
+	/// In-game time in seconds.
+	pub time: f32,
+	/// Amount of minerals bot has.
+	pub minerals: u32,
+	/// Amount of gas bot has.
+	pub vespene: u32,
+	/// Total supply used.
+	pub supply_used: u32,
+	/// The supply limit.
+	pub supply_cap: u32,
+	/// Amount of supply used by army.
+	pub supply_army: u32,
+	/// Amount of supply used by workers.
+	pub supply_workers: u32,
+	/// Ready owned units counted by unit type.
+	pub my_units: FxHashMap<UnitTypeId, usize>,
+	/// In-progress owned units counted by unit type.
+	pub my_units_ordered: FxHashMap<UnitTypeId, usize>,
+	/// Ready visible enemy units counted by unit type.
+	pub enemy_units: FxHashMap<UnitTypeId, usize>,
+	/// Bot's ready upgrades.
+	pub upgrades: Vec<UpgradeId>,
+	/// All visible units, only present if requested with [`Bot::snapshot_with_units`].
+	pub units: Option<Vec<UnitSnapshot>>,
+}
+impl GameSnapshot {
+	pub(crate) fn new(bot: &Bot, with_units: bool) -> Self {
+		let mut my_units_ordered = FxHashMap::default();
+		for u in bot.units.my.all.iter().filter(|u| !u.is_ready()) {
+			*my_units_ordered.entry(u.type_id()).or_default() += 1;
+		}
+		let upgrades = bot.state.observation.raw.upgrades.read_lock();
+
+		Self {
+			version: SNAPSHOT_VERSION,
+			game_loop: bot.state.observation.game_loop(),
+			time: bot.time,
+			minerals: bot.minerals,
+			vespene: bot.vespene,
+			supply_used: bot.supply_used,
+			supply_cap: bot.supply_cap,
+			supply_army: bot.supply_army,
+			supply_workers: bot.supply_workers,
+			my_units: bot.current_units.clone(),
+			my_units_ordered,
+			enemy_units: bot.enemies_current.clone(),
+			upgrades: upgrades.iter().copied().collect(),
+			units: if with_units {
+				Some(bot.units.all.iter().map(UnitSnapshot::new).collect())
+			} else {
+				None
+			},
+		}
+	}
+}
+
+/// Short summary of unit in [`GameSnapshot`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct UnitSnapshot {
+	/// Unique and constant for each unit tag.
+	pub tag: u64,
+	/// The type of unit.
+	pub type_id: UnitTypeId,
+	/// Player id of the owner.
+	pub owner: u32,
+	/// Position on the map as `(x, y)`.
+	pub position: (f32, f32),
+	/// Current hit points of unit (if known).
+	pub health: Option<u32>,
+	/// Current shield of protoss unit (if known).
+	pub shield: Option<u32>,
+	/// Completion status of building or unit in production (from `0.0` to `1.0`).
+	pub build_progress: f32,
+}
+impl UnitSnapshot {
+	fn new(u: &Unit) -> Self {
+		let pos = u.position();
+		Self {
+			tag: u.tag(),
+			type_id: u.type_id(),
+			owner: u.owner(),
+			position: (pos.x, pos.y),
+			health: u.health(),
+			shield: u.shield(),
+			build_progress: u.build_progress(),
+		}
+	}
+}
@@ -0,0 +1,96 @@
+//! Spatial index over units, used to speed up range queries.
+//!
+//! Bot keeps a [`SpatialGrid`] over [`units.all`](crate::units::AllUnits::all), which is rebuilt lazily
+//! on the first query of each step. Use [`Bot::query_radius`] and [`Bot::query_rect`] to search in it.
+//!
+//! [`Bot::query_radius`]: crate::bot::Bot::query_radius
+//! [`Bot::query_rect`]: crate::bot::Bot::query_rect
+
+use crate::{distance::Distance, geometry::Point2, units::Units};
+use rustc_hash::FxHashMap;
+
+/// Size of grid cell used by bot's spatial index.
+///
+/// Smaller cells make each query check less extra units, but queries with big radius
+/// have to visit more cells and building the grid takes more memory.
+/// Cells around the size of typical weapon range (plus unit radiuses) work best for combat queries.
+pub const DEFAULT_CELL_SIZE: f32 = 8.0;
+
+/// Uniform grid of unit tags bucketed by their positions.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+	cell_size: f32,
+	cells: FxHashMap<(i32, i32), Vec<u64>>,
+	max_radius: f32,
+}
+impl SpatialGrid {
+	/// Builds grid with given cell size over given units.
+	pub fn new(units: &Units, cell_size: f32) -> Self {
+		let mut grid = Self {
+			cell_size,
+			cells: Default::default(),
+			max_radius: 0.0,
+		};
+		for u in units {
+			grid.cells
+				.entry(grid.cell(u.position()))
+				.or_default()
+				.push(u.tag());
+			grid.max_radius = grid.max_radius.max(u.radius());
+		}
+		grid
+	}
+	/// Returns size of grid cell.
+	pub fn cell_size(&self) -> f32 {
+		self.cell_size
+	}
+	/// Returns radius of the biggest unit in the grid.
+	pub fn max_radius(&self) -> f32 {
+		self.max_radius
+	}
+
+	fn cell(&self, pos: Point2) -> (i32, i32) {
+		(
+			(pos.x / self.cell_size).floor() as i32,
+			(pos.y / self.cell_size).floor() as i32,
+		)
+	}
+
+	/// Returns tags of units in cells overlapping given rectangle.
+	///
+	/// Result is a superset of units in the rectangle, so exact check should be done by caller.
+	pub fn candidates(&self, p0: Point2, p1: Point2) -> impl Iterator<Item = u64> + '_ {
+		let (x0, y0) = self.cell(Point2::new(p0.x.min(p1.x), p0.y.min(p1.y)));
+		let (x1, y1) = self.cell(Point2::new(p0.x.max(p1.x), p0.y.max(p1.y)));
+		(x0..=x1)
+			.flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+			.filter_map(move |cell| self.cells.get(&cell))
+			.flatten()
+			.copied()
+	}
+	/// Returns units from given collection with centers in `radius` from `center`.
+	///
+	/// Given collection should be the one grid was built over (or contain it).
+	pub fn query_radius(&self, units: &Units, center: Point2, radius: f32) -> Units {
+		self.candidates(center - radius, center + radius)
+			.filter_map(|tag| units.get(tag))
+			.filter(|u| u.position().distance_squared(center) <= radius * radius)
+			.cloned()
+			.collect()
+	}
+	/// Returns units from given collection with centers inside of rectangle with given corners.
+	///
+	/// Given collection should be the one grid was built over (or contain it).
+	pub fn query_rect(&self, units: &Units, p0: Point2, p1: Point2) -> Units {
+		let (min_x, max_x) = (p0.x.min(p1.x), p0.x.max(p1.x));
+		let (min_y, max_y) = (p0.y.min(p1.y), p0.y.max(p1.y));
+		self.candidates(p0, p1)
+			.filter_map(|tag| units.get(tag))
+			.filter(|u| {
+				let pos = u.position();
+				(min_x..=max_x).contains(&pos.x) && (min_y..=max_y).contains(&pos.y)
+			})
+			.cloned()
+			.collect()
+	}
+}
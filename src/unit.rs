@@ -5,8 +5,9 @@ use crate::{
 	action::{Commander, Target},
 	bot::{LockBool, LockOwned, LockU32, Locked, Reader, Rl, Rs, Rw},
 	consts::{
-		RaceValues, ANTI_ARMOR_BUFF, DAMAGE_BONUS_PER_UPGRADE, FRAMES_PER_SECOND, MISSED_WEAPONS,
-		OFF_CREEP_SPEED_UPGRADES, SPEED_BUFFS, SPEED_ON_CREEP, SPEED_UPGRADES, WARPGATE_ABILITIES,
+		RaceValues, ANTI_ARMOR_BUFF, BUILDING_FOOTPRINTS, DAMAGE_BONUS_PER_UPGRADE, FRAMES_PER_SECOND,
+		MISSED_WEAPONS, OFF_CREEP_SPEED_UPGRADES, SPEED_BUFFS, SPEED_ON_CREEP, SPEED_UPGRADES, UNIT_ALIAS,
+		WARPGATE_ABILITIES,
 	},
 	distance::Distance,
 	game_data::{Attribute, Cost, GameData, TargetType, UnitTypeData, Weapon},
@@ -15,7 +16,7 @@ use crate::{
 	ids::{AbilityId, BuffId, UnitTypeId, UpgradeId},
 	pixel_map::{PixelMap, VisibilityMap},
 	player::Race,
-	units::Container,
+	units::{Container, Units},
 	utils::CacheMap,
 	FromProto,
 };
@@ -37,6 +38,7 @@ pub(crate) struct DataForUnit {
 	pub race_values: Rs<RaceValues>,
 	pub max_cooldowns: Rw<FxHashMap<UnitTypeId, f32>>,
 	pub last_units_health: Rw<FxHashMap<u64, u32>>,
+	pub last_units_position: Rw<FxHashMap<u64, (Point2, u32)>>,
 	pub abilities_units: Rw<FxHashMap<u64, FxHashSet<AbilityId>>>,
 	pub upgrades: Rw<FxHashSet<UpgradeId>>,
 	pub enemy_upgrades: Rw<FxHashSet<UpgradeId>>,
@@ -148,6 +150,14 @@ impl Unit {
 	pub fn type_id(&self) -> UnitTypeId {
 		*self.base.type_id.read_lock()
 	}
+	/// Checks if unit is of given type or of its alias
+	/// (e.g. `SiegeTankSieged` matches `SiegeTank`, `WidowMineBurrowed` matches `WidowMine`).
+	pub fn is_same_unit_type_as(&self, other: UnitTypeId) -> bool {
+		let unit_type = self.type_id();
+		unit_type == other
+			|| UNIT_ALIAS.get(&unit_type) == Some(&other)
+			|| UNIT_ALIAS.get(&other) == Some(&unit_type)
+	}
 	/// Player id of the owner. Normally it should match your [`player_id`] for owned units
 	/// and [`enemy_player_id`] for opponent's units.
 	///
@@ -173,7 +183,10 @@ impl Unit {
 	pub fn facing(&self) -> f32 {
 		self.base.facing
 	}
-	/// Radius of the unit.
+	/// Radius of the unit, as reported by the game.
+	///
+	/// It's a collision radius, which is used in range checks.
+	/// For placement size of buildings use [`footprint`](Self::footprint) instead.
 	#[inline]
 	pub fn radius(&self) -> f32 {
 		self.base.radius
@@ -297,14 +310,14 @@ impl Unit {
 	}
 	/// Amount of minerals left in mineral field.
 	///
-	/// Note: Not populated for snapshots.
+	/// Note: Not populated for snapshots, so it's known only while resource is in vision.
 	#[inline]
 	pub fn mineral_contents(&self) -> Option<u32> {
 		self.base.mineral_contents
 	}
 	/// Amount of vespene gas left in vespene geyser.
 	///
-	/// Note: Not populated for snapshots.
+	/// Note: Not populated for snapshots, so it's known only while resource is in vision.
 	#[inline]
 	pub fn vespene_contents(&self) -> Option<u32> {
 		self.base.vespene_contents
@@ -341,6 +354,16 @@ impl Unit {
 	pub fn orders(&self) -> &[UnitOrder] {
 		&self.base.orders
 	}
+	/// Number of unit's current orders (including queued ones).
+	///
+	/// Can be compared with number of issued commands on the next step
+	/// to detect units which ignored a command.
+	///
+	/// Note: Not populated for enemies.
+	#[inline]
+	pub fn order_count(&self) -> usize {
+		self.base.orders.len()
+	}
 	/// Tag of addon if any.
 	///
 	/// Note: Not populated for enemies.
@@ -369,6 +392,16 @@ impl Unit {
 	pub fn cargo_space_max(&self) -> Option<u32> {
 		self.base.cargo_space_max
 	}
+	/// Used space of transport or bunker, `0` if unit has no cargo.
+	#[inline]
+	pub fn cargo_used(&self) -> u32 {
+		self.cargo_space_taken().unwrap_or(0)
+	}
+	/// Maximum space of transport or bunker, `0` if unit has no cargo.
+	#[inline]
+	pub fn cargo_max(&self) -> u32 {
+		self.cargo_space_max().unwrap_or(0)
+	}
 	/// Current number of workers on gas or base.
 	///
 	/// Note: Not populated for enemies.
@@ -559,6 +592,19 @@ impl Unit {
 			self.footprint_radius().map(|radius| (radius * 2.0) as usize)
 		}
 	}
+	/// Placement size of building in tiles (side of square footprint):
+	/// `1` for sensor tower and creep tumor, `2` for supply and addons, `3` for barracks,
+	/// `5` for townhalls.
+	///
+	/// Taken from [`BUILDING_FOOTPRINTS`],
+	/// falls back to [`building_size`](Self::building_size) for types missing there.
+	/// Returns `0.0` for non-structures.
+	pub fn footprint(&self) -> f32 {
+		match BUILDING_FOOTPRINTS.get(&self.type_id()) {
+			Some(size) => *size as f32,
+			None => self.building_size().unwrap_or(0) as f32,
+		}
+	}
 	/// How long a unit takes to build.
 	pub fn build_time(&self) -> f32 {
 		self.type_data().map_or(0.0, |data| data.build_time)
@@ -585,6 +631,8 @@ impl Unit {
 		self.display_type().is_visible()
 	}
 	/// Checks if unit is snapshot (i.e. hidden in fog of war or on high ground).
+	///
+	/// Snapshot is the last known state of unit, it may be not there anymore.
 	pub fn is_snapshot(&self) -> bool {
 		self.display_type().is_snapshot()
 	}
@@ -696,6 +744,17 @@ impl Unit {
 		}
 		Some(current as f32 / max as f32)
 	}
+	/// Returns health percentage in range from `0` to `1`, or `1` if it's unknown (e.g. for snapshots).
+	///
+	/// Use [`shield_percentage`](Self::shield_percentage) for shields,
+	/// which is `None` for units without them.
+	pub fn health_percent(&self) -> f32 {
+		self.health_percentage().unwrap_or(1.0)
+	}
+	/// Returns summed health and shield, or `0` if it's unknown (e.g. for snapshots).
+	pub fn total_health(&self) -> f32 {
+		self.hits().unwrap_or(0) as f32
+	}
 	/// Basic speed of the unit without considering buffs and upgrades.
 	///
 	/// Use [`real_speed`](Self::real_speed) to get speed including buffs and upgrades.
@@ -759,11 +818,47 @@ impl Unit {
 	pub fn distance_to_weapon_ready(&self) -> f32 {
 		self.real_speed() / FRAMES_PER_SECOND * self.weapon_cooldown().unwrap_or(0.0)
 	}
+	/// Movement of the unit per game loop since the previous step,
+	/// or `None` if unit wasn't visible on previous step.
+	pub fn velocity(&self) -> Option<Point2> {
+		let (last_pos, last_loop) = *self.data.last_units_position.read_lock().get(&self.tag())?;
+		let game_loop = self.data.game_loop.get_locked();
+		if game_loop <= last_loop {
+			return None;
+		}
+		Some((self.position() - last_pos) / (game_loop - last_loop) as f32)
+	}
+	/// Predicts position of the unit after given number of game loops.
+	///
+	/// Assumes unit moves in a straight line with constant speed:
+	/// own units with position target of current order move towards it
+	/// with [`real_speed`](Self::real_speed) (stopping there),
+	/// other units keep their [`velocity`](Self::velocity) observed since the previous step.
+	/// If unit's motion is unknown (e.g. it just appeared), its current position is returned.
+	pub fn predicted_position(&self, frames: f32) -> Point2 {
+		let pos = self.position();
+		if let Some(target) = self.target_pos() {
+			let distance = self.real_speed() / FRAMES_PER_SECOND * frames;
+			return if pos.distance(target) <= distance {
+				target
+			} else {
+				pos.towards(target, distance)
+			};
+		}
+		match self.velocity() {
+			Some(velocity) => pos + velocity * frames,
+			None => pos,
+		}
+	}
 	/// Attributes of unit, dependent on it's type.
 	pub fn attributes(&self) -> &[Attribute] {
 		self.type_data().map_or(&[], |data| data.attributes.as_slice())
 	}
 	/// Checks if unit has given attribute.
+	///
+	/// There're also shortcuts for each attribute, like [`is_light`](Self::is_light),
+	/// [`is_armored`](Self::is_armored), [`is_structure`](Self::is_structure), etc.
+	/// Note that [`is_flying`](Self::is_flying) isn't an attribute and is taken from observation.
 	pub fn has_attribute(&self, attribute: Attribute) -> bool {
 		self.type_data()
 			.map_or(false, |data| data.attributes.contains(&attribute))
@@ -1464,6 +1559,26 @@ impl Unit {
 		(if speed == 0.0 { 0.0 } else { damage as f32 / speed }, range)
 	}
 
+	/// Returns closest to this unit from given units.
+	///
+	/// Mirrors [`Units::closest`], so `unit.closest(&units)` is the same as `units.closest(unit)`.
+	pub fn closest<'a>(&self, others: &'a Units) -> Option<&'a Unit> {
+		others.closest(self)
+	}
+	/// Returns distance from this unit to closest of given units.
+	///
+	/// Mirrors [`Units::closest_distance`].
+	pub fn distance_to_closest(&self, others: &Units) -> Option<f32> {
+		others.closest_distance(self)
+	}
+	/// Checks if distance between this unit and `other` is less than given `distance`.
+	///
+	/// The same as [`Distance::is_closer`], but doesn't require `Distance` trait to be in scope.
+	#[inline]
+	pub fn is_closer<P: Into<Point2>>(&self, distance: f32, other: P) -> bool {
+		Distance::is_closer(self, distance, other)
+	}
+
 	/// Checks if unit is close enough to attack given target.
 	///
 	/// See also [`in_real_range`](Self::in_real_range) which uses actual range of unit for calculations.
@@ -1555,14 +1670,14 @@ impl Unit {
 	pub fn target(&self) -> Target {
 		self.orders().first().map_or(Target::None, |order| order.target)
 	}
-	/// Returns target point of unit's order if any.
+	/// Returns target point of unit's current order if it's a position.
 	pub fn target_pos(&self) -> Option<Point2> {
 		match self.target() {
 			Target::Pos(pos) => Some(pos),
 			_ => None,
 		}
 	}
-	/// Returns target tag of unit's order if any.
+	/// Returns target tag of unit's current order if it's a unit.
 	pub fn target_tag(&self) -> Option<u64> {
 		match self.target() {
 			Target::Tag(tag) => Some(tag),
@@ -1611,24 +1726,26 @@ impl Unit {
 	pub fn is_using_any<A: Container<AbilityId>>(&self, abilities: &A) -> bool {
 		self.ordered_ability().map_or(false, |a| abilities.contains(&a))
 	}
+	/// Classifies first order of the unit, see [`OrderKind`].
+	///
+	/// Doesn't work with enemies.
+	pub fn order_kind(&self) -> OrderKind {
+		match self.ordered_ability() {
+			None => OrderKind::Idle,
+			Some(ability) => OrderKind::from(ability),
+		}
+	}
 	/// Checks if unit is currently attacking.
 	///
 	/// Doesn't work with enemies.
-	#[rustfmt::skip::macros(matches)]
 	pub fn is_attacking(&self) -> bool {
-		self.is_using_any(&vec![
-			AbilityId::Attack,
-			AbilityId::AttackAttack,
-			AbilityId::AttackAttackTowards,
-			AbilityId::AttackAttackBarrage,
-			AbilityId::ScanMove,
-		])
+		self.order_kind() == OrderKind::Attacking
 	}
 	/// Checks if unit is currently moving.
 	///
 	/// Doesn't work with enemies.
 	pub fn is_moving(&self) -> bool {
-		self.is_using(AbilityId::MoveMove)
+		self.order_kind() == OrderKind::Moving
 	}
 	/// Checks if unit is currently patrolling.
 	///
@@ -1646,23 +1763,13 @@ impl Unit {
 	///
 	/// Doesn't work with enemies.
 	pub fn is_gathering(&self) -> bool {
-		self.is_using_any(&vec![
-			AbilityId::HarvestGatherSCV,
-			AbilityId::HarvestGatherMule,
-			AbilityId::HarvestGatherDrone,
-			AbilityId::HarvestGatherProbe,
-		])
+		self.order_kind() == OrderKind::Gathering
 	}
 	/// Checks if worker is currently returning resource closest base.
 	///
 	/// Doesn't work with enemies.
 	pub fn is_returning(&self) -> bool {
-		self.is_using_any(&vec![
-			AbilityId::HarvestReturnSCV,
-			AbilityId::HarvestReturnMule,
-			AbilityId::HarvestReturnDrone,
-			AbilityId::HarvestReturnProbe,
-		])
+		self.order_kind() == OrderKind::Returning
 	}
 	/// Checks if worker is currently gathering or returning resources.
 	///
@@ -2084,6 +2191,46 @@ impl FromProto<ProtoDisplayType> for DisplayType {
 	}
 }
 
+/// Kind of unit's current order. Can be accessed through [`order_kind`](Unit::order_kind) method.
+#[variant_checkers]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OrderKind {
+	/// Unit doesn't have any orders.
+	Idle,
+	/// Worker is gathering resource.
+	Gathering,
+	/// Worker is returning resource to the closest base.
+	Returning,
+	/// Unit is attacking or attack-moving.
+	Attacking,
+	/// Unit is moving.
+	Moving,
+	/// Any other order.
+	Other,
+}
+
+impl From<AbilityId> for OrderKind {
+	fn from(ability: AbilityId) -> Self {
+		match ability {
+			AbilityId::HarvestGatherSCV
+			| AbilityId::HarvestGatherMule
+			| AbilityId::HarvestGatherDrone
+			| AbilityId::HarvestGatherProbe => OrderKind::Gathering,
+			AbilityId::HarvestReturnSCV
+			| AbilityId::HarvestReturnMule
+			| AbilityId::HarvestReturnDrone
+			| AbilityId::HarvestReturnProbe => OrderKind::Returning,
+			AbilityId::Attack
+			| AbilityId::AttackAttack
+			| AbilityId::AttackAttackTowards
+			| AbilityId::AttackAttackBarrage
+			| AbilityId::ScanMove => OrderKind::Attacking,
+			AbilityId::MoveMove => OrderKind::Moving,
+			_ => OrderKind::Other,
+		}
+	}
+}
+
 /// Order given to unit. All current orders of unit stored in [`orders`](Unit::orders) field.
 #[derive(Clone)]
 pub struct UnitOrder {
@@ -2136,3 +2283,51 @@ impl Radius for Unit {
 		self.radius()
 	}
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::pixel_map::Visibility;
+	use num_traits::ToPrimitive;
+	use sc2_proto::raw::Alliance as ProtoAlliance;
+
+	/// Makes complete visible own unit of given type at given position.
+	pub(crate) fn proto_unit(tag: u64, type_id: UnitTypeId, x: f32, y: f32) -> ProtoUnit {
+		let mut u = ProtoUnit::new();
+		u.set_tag(tag);
+		u.set_unit_type(type_id.to_u32().unwrap());
+		u.set_alliance(ProtoAlliance::value_Self);
+		u.set_display_type(ProtoDisplayType::Visible);
+		u.set_build_progress(1.0);
+		let pos = u.mut_pos();
+		pos.set_x(x);
+		pos.set_y(y);
+		u
+	}
+	/// Makes unit from proto with given shared data, everything on the map is visible.
+	pub(crate) fn make_unit(data: &SharedUnitData, u: &ProtoUnit) -> Unit {
+		let visibility = VisibilityMap::from_elem((64, 64), Visibility::Visible);
+		Unit::from_proto(Rs::clone(data), &visibility, u)
+	}
+	pub(crate) fn unit(tag: u64, type_id: UnitTypeId) -> Unit {
+		make_unit(&Default::default(), &proto_unit(tag, type_id, 10.0, 10.0))
+	}
+
+	#[test]
+	fn same_unit_type_as_alias() {
+		let tank = unit(1, UnitTypeId::SiegeTank);
+		let sieged = unit(2, UnitTypeId::SiegeTankSieged);
+		assert!(tank.is_same_unit_type_as(UnitTypeId::SiegeTank));
+		assert!(tank.is_same_unit_type_as(UnitTypeId::SiegeTankSieged));
+		assert!(sieged.is_same_unit_type_as(UnitTypeId::SiegeTank));
+		assert!(sieged.is_same_unit_type_as(UnitTypeId::SiegeTankSieged));
+
+		let mine = unit(3, UnitTypeId::WidowMine);
+		let burrowed = unit(4, UnitTypeId::WidowMineBurrowed);
+		assert!(mine.is_same_unit_type_as(UnitTypeId::WidowMineBurrowed));
+		assert!(burrowed.is_same_unit_type_as(UnitTypeId::WidowMine));
+
+		assert!(!tank.is_same_unit_type_as(UnitTypeId::WidowMine));
+		assert!(!burrowed.is_same_unit_type_as(UnitTypeId::SiegeTankSieged));
+	}
+}
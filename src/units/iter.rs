@@ -1,7 +1,10 @@
 //! Iterator adaptors for Units.
 
 use super::Container;
-use crate::{ids::UnitTypeId, unit::Unit};
+use crate::{
+	ids::{BuffId, UnitTypeId},
+	unit::{OrderKind, Unit},
+};
 use indexmap::map::IntoIter;
 use std::borrow::Borrow;
 
@@ -228,6 +231,24 @@ impl<I> ExcludeType<I> {
 }
 impl_simple_iterator!(ExcludeType);
 
+/// An iterator that filters units with given buff.
+#[derive(Clone)]
+pub struct WithBuff<I> {
+	iter: I,
+	buff: BuffId,
+}
+impl<I> WithBuff<I> {
+	pub(super) fn new(iter: I, buff: BuffId) -> Self {
+		Self { iter, buff }
+	}
+
+	fn predicate(&self) -> impl Fn(&Unit) -> bool {
+		let buff = self.buff;
+		move |u| u.has_buff(buff)
+	}
+}
+impl_simple_iterator!(WithBuff);
+
 /// An iterator that filters units of given types.
 #[derive(Clone)]
 pub struct OfTypes<'a, I, T> {
@@ -357,6 +378,54 @@ make_simple_iterator!(
 	|u| u.is_visible()
 );
 
+make_simple_iterator!(
+	/// An iterator that filters snapshots (units last seen in fog of war).
+	Snapshots,
+	|u| u.is_snapshot()
+);
+
+make_simple_iterator!(
+	/// An iterator that filters fully hidden units.
+	Hidden,
+	|u| u.is_hidden()
+);
+
+make_simple_iterator!(
+	/// An iterator that filters workers gathering resources.
+	Gathering,
+	|u| u.order_kind() == OrderKind::Gathering
+);
+
+make_simple_iterator!(
+	/// An iterator that filters workers returning resources.
+	Returning,
+	|u| u.order_kind() == OrderKind::Returning
+);
+
+make_simple_iterator!(
+	/// An iterator that filters units which are attacking.
+	Attacking,
+	|u| u.order_kind() == OrderKind::Attacking
+);
+
+make_simple_iterator!(
+	/// An iterator that filters units which are moving.
+	Moving,
+	|u| u.order_kind() == OrderKind::Moving
+);
+
+make_simple_iterator!(
+	/// An iterator that filters detectors.
+	Detectors,
+	|u| u.is_detector()
+);
+
+make_simple_iterator!(
+	/// An iterator that filters structures.
+	Structures,
+	|u| u.is_structure()
+);
+
 /// An iterator that filters units in attack range of given unit.
 #[derive(Clone)]
 pub struct InRangeOf<'a, I> {
@@ -504,6 +573,42 @@ where
 	fn visible(self) -> Visible<Self> {
 		Visible::new(self)
 	}
+	/// Leaves only snapshots (units remembered in fog of war, which may be not there anymore).
+	fn snapshots(self) -> Snapshots<Self> {
+		Snapshots::new(self)
+	}
+	/// Leaves only fully hidden units (with [`Hidden`](crate::unit::DisplayType::Hidden) display type).
+	fn hidden(self) -> Hidden<Self> {
+		Hidden::new(self)
+	}
+	/// Leaves only workers which are gathering resources.
+	fn gathering(self) -> Gathering<Self> {
+		Gathering::new(self)
+	}
+	/// Leaves only workers which are returning resources.
+	fn returning(self) -> Returning<Self> {
+		Returning::new(self)
+	}
+	/// Leaves only units which are attacking.
+	fn attacking(self) -> Attacking<Self> {
+		Attacking::new(self)
+	}
+	/// Leaves only units which are moving.
+	fn moving(self) -> Moving<Self> {
+		Moving::new(self)
+	}
+	/// Leaves only detectors.
+	fn detectors(self) -> Detectors<Self> {
+		Detectors::new(self)
+	}
+	/// Leaves only structures.
+	fn structures(self) -> Structures<Self> {
+		Structures::new(self)
+	}
+	/// Leaves only units with given buff.
+	fn with_buff(self, buff: BuffId) -> WithBuff<Self> {
+		WithBuff::new(self, buff)
+	}
 	/// Leaves only units in attack range of given unit.
 	fn in_range_of(self, unit: &Unit, gap: f32) -> InRangeOf<Self> {
 		InRangeOf::new(self, unit, gap)
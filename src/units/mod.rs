@@ -1,13 +1,17 @@
 //! Data structures for storing units, fast filtering and finding ones that needed.
 #![warn(missing_docs)]
 
-use crate::{geometry::Point2, ids::UnitTypeId, unit::Unit};
+use crate::{
+	geometry::Point2,
+	ids::{BuffId, UnitTypeId},
+	unit::{OrderKind, Unit},
+};
 use indexmap::{
 	map::{Iter, IterMut, Keys, Values, ValuesMut},
 	IndexMap, IndexSet,
 };
 use iter::IntoUnits;
-use rustc_hash::FxHasher;
+use rustc_hash::{FxHashMap, FxHasher};
 use std::{
 	hash::BuildHasherDefault,
 	iter::FromIterator,
@@ -96,6 +100,13 @@ impl PlayerUnits {
 
 /// Convinient [`Unit`]s collection.
 ///
+/// Units are indexed by their tags, so collection never contains duplicates:
+/// when collections are merged (with [`extend`](Extend::extend), [`collect`](Iterator::collect)
+/// or [`push`](Self::push)) only one unit per tag is kept: the unit added later replaces
+/// the earlier one, but stays at position where the tag was inserted first.
+/// Because of this no extra deduplication is needed, e.g. after combining
+/// [`anti_air_units`](crate::bot::Bot::anti_air_units) with [`detectors`](Self::detectors).
+///
 // [`Unit`]: crate::unit::Unit
 #[derive(Default, Clone)]
 pub struct Units(FxIndexMap<u64, Unit>);
@@ -191,6 +202,8 @@ impl Units {
 	}
 
 	/// Returns an iterator over unit tags of the collection.
+	///
+	/// Can be used with [`command_tags`](crate::bot::Bot::command_tags) to order all units at once.
 	#[inline]
 	pub fn tags(&self) -> Keys<u64, Unit> {
 		self.0.keys()
@@ -256,6 +269,13 @@ impl Units {
 	pub fn of_type(&self, unit_type: UnitTypeId) -> Self {
 		self.filter(|u| u.type_id() == unit_type)
 	}
+	/// Leaves only units of given type or of its alias and makes a new collection of them
+	/// (e.g. sieged tanks are included when filtering by `SiegeTank`).
+	///
+	/// See [`Unit::is_same_unit_type_as`].
+	pub fn of_type_including_alias(&self, unit_type: UnitTypeId) -> Self {
+		self.filter(|u| u.is_same_unit_type_as(unit_type))
+	}
 	/// Excludes all units of given type and makes a new collection of remaining units.
 	///
 	/// Warning: This method will clone units in order to create a new collection
@@ -274,6 +294,32 @@ impl Units {
 			Some(self.sum(|u| u.position()) / self.len() as f32)
 		}
 	}
+	/// Returns center of units weighted by given function
+	/// or `None` if collection is empty or total weight is zero.
+	pub fn weighted_center<F>(&self, weight: F) -> Option<Point2>
+	where
+		F: Fn(&Unit) -> f32,
+	{
+		let (sum, total) = self.iter().fold((Point2::default(), 0.0), |(sum, total), u| {
+			let w = weight(u);
+			(sum + u.position() * w, total + w)
+		});
+		if total > 0.0 {
+			Some(sum / total)
+		} else {
+			None
+		}
+	}
+	/// Returns center of units weighted by their supply cost, i.e. center of army's force.
+	///
+	/// Units without supply cost (e.g. structures) are ignored.
+	pub fn center_by_supply(&self) -> Option<Point2> {
+		self.weighted_center(|u| u.supply_cost())
+	}
+	/// Returns center of units weighted by their hits (health + shield).
+	pub fn center_by_health(&self) -> Option<Point2> {
+		self.weighted_center(|u| u.hits().unwrap_or(0) as f32)
+	}
 	/// Leaves only non-flying units and makes new collection of them.
 	///
 	/// Warning: This method will clone units in order to create a new collection
@@ -334,6 +380,46 @@ impl Units {
 	pub fn almost_idle(&self) -> Self {
 		self.filter(|u| u.is_almost_idle())
 	}
+	/// Leaves only workers gathering resources and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`gathering`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`gathering`]: iter::UnitsIterator::gathering
+	pub fn gathering(&self) -> Self {
+		self.filter(|u| u.order_kind() == OrderKind::Gathering)
+	}
+	/// Leaves only workers returning resources and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`returning`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`returning`]: iter::UnitsIterator::returning
+	pub fn returning(&self) -> Self {
+		self.filter(|u| u.order_kind() == OrderKind::Returning)
+	}
+	/// Leaves only units which are attacking and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`attacking`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`attacking`]: iter::UnitsIterator::attacking
+	pub fn attacking(&self) -> Self {
+		self.filter(|u| u.order_kind() == OrderKind::Attacking)
+	}
+	/// Leaves only units which are moving and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`moving`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`moving`]: iter::UnitsIterator::moving
+	pub fn moving(&self) -> Self {
+		self.filter(|u| u.order_kind() == OrderKind::Moving)
+	}
 	/// Leaves only units with no orders and makes new collection of them.
 	/// Unlike [`idle`] this takes reactor on terran buildings into account.
 	///
@@ -403,6 +489,7 @@ impl Units {
 		self.filter(|u| u.in_real_range(unit, gap))
 	}
 	/// Leaves only units visible on current step and makes new collection of them.
+	/// Use it to drop snapshots before choosing targets to attack.
 	///
 	/// Warning: This method will clone units in order to create a new collection
 	/// and will be evaluated initially. When applicable prefer using [`visible`]
@@ -412,6 +499,144 @@ impl Units {
 	pub fn visible(&self) -> Self {
 		self.filter(|u| u.is_visible())
 	}
+	/// Leaves only snapshots and makes new collection of them.
+	///
+	/// Snapshots are units remembered in fog of war (mostly enemy structures),
+	/// they may be not there anymore, so shouldn't be targeted by attacks.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`snapshots`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`snapshots`]: iter::UnitsIterator::snapshots
+	pub fn snapshots(&self) -> Self {
+		self.filter(|u| u.is_snapshot())
+	}
+	/// Leaves only fully hidden units (with [`Hidden`](crate::unit::DisplayType::Hidden) display type)
+	/// and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`hidden`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`hidden`]: iter::UnitsIterator::hidden
+	pub fn hidden(&self) -> Self {
+		self.filter(|u| u.is_hidden())
+	}
+	/// Leaves only detectors and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`detectors`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`detectors`]: iter::UnitsIterator::detectors
+	pub fn detectors(&self) -> Self {
+		self.filter(|u| u.is_detector())
+	}
+	/// Leaves only structures and makes new collection of them.
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`structures`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`structures`]: iter::UnitsIterator::structures
+	pub fn structures(&self) -> Self {
+		self.filter(|u| u.is_structure())
+	}
+	/// Leaves only units with given buff and makes new collection of them.
+	///
+	/// Useful to react on enemy spells or to track own buffed units, for example:
+	/// ```
+	/// use rust_sc2::{bot::Bot, consts::ANTI_ARMOR_BUFF, prelude::*};
+	///
+	/// // Own units, which should get out of Fungal Growth or be cleansed of anti-armor missile.
+	/// fn units_to_cleanse(bot: &Bot) -> Units {
+	///     let mut units = bot.units.my.units.with_buff(BuffId::FungalGrowth);
+	///     units.extend(bot.units.my.units.with_buff(ANTI_ARMOR_BUFF));
+	///     units
+	/// }
+	/// ```
+	///
+	/// Warning: This method will clone units in order to create a new collection
+	/// and will be evaluated initially. When applicable prefer using [`with_buff`]
+	/// on the iterator over units, since it's lazily evaluated and doesn't do any cloning operations.
+	///
+	/// [`with_buff`]: iter::UnitsIterator::with_buff
+	pub fn with_buff(&self, buff: BuffId) -> Self {
+		self.filter(|u| u.has_buff(buff))
+	}
+
+	/// Leaves only units that match given predicate and makes new collection of them.
+	///
+	/// Unlike [`filter`](Self::filter) predicate takes `&Unit` instead of `&&Unit`,
+	/// so it's easier to pass functions and closures defined somewhere else.
+	pub fn filter_fn<F>(&self, f: F) -> Self
+	where
+		F: Fn(&Unit) -> bool,
+	{
+		self.iter().filter(|u| f(u)).cloned().collect()
+	}
+	/// Splits the collection into two: units that match given predicate and units that don't.
+	pub fn partition_fn<F>(&self, f: F) -> (Self, Self)
+	where
+		F: Fn(&Unit) -> bool,
+	{
+		self.iter().cloned().partition(|u| f(u))
+	}
+
+	/// Splits the collection into groups of units with the same type.
+	pub fn group_by_type(&self) -> FxHashMap<UnitTypeId, Units> {
+		let mut groups = FxHashMap::<UnitTypeId, Units>::default();
+		for u in self.iter() {
+			groups.entry(u.type_id()).or_default().push(u.clone());
+		}
+		groups
+	}
+	/// Counts units of each type in the collection.
+	pub fn counts_by_type(&self) -> FxHashMap<UnitTypeId, usize> {
+		let mut counts = FxHashMap::default();
+		for u in self.iter() {
+			*counts.entry(u.type_id()).or_default() += 1;
+		}
+		counts
+	}
+
+	/// Makes new collection of units present in either `self` or `other` collection.
+	///
+	/// Units are deduplicated by tag, if unit present in both collections, one from `self` is taken.
+	pub fn union(&self, other: &Units) -> Self {
+		let mut units = self.clone();
+		units.extend(other.iter().filter(|u| !self.contains_tag(u.tag())).cloned());
+		units
+	}
+	/// Makes new collection of units from `self` which are not present in `other` collection.
+	pub fn difference(&self, other: &Units) -> Self {
+		self.iter()
+			.filter(|u| !other.contains_tag(u.tag()))
+			.cloned()
+			.collect()
+	}
+	/// Makes new collection of units from `self` which are also present in `other` collection.
+	pub fn intersection(&self, other: &Units) -> Self {
+		self.iter()
+			.filter(|u| other.contains_tag(u.tag()))
+			.cloned()
+			.collect()
+	}
+	/// Makes new collection of units which tags are in given set, order of units is preserved.
+	///
+	/// Unlike [`find_tags`](Self::find_tags) iterates over units instead of tags,
+	/// so it's better when set is big (e.g. tags of units with some role).
+	pub fn filter_tags<S: BuildHasher>(&self, tags: &HashSet<u64, S>) -> Self {
+		self.iter().filter(|u| tags.contains(&u.tag())).cloned().collect()
+	}
+	/// Makes new collection of units which tags are not in given set.
+	pub fn exclude_tags<S: BuildHasher>(&self, tags: &HashSet<u64, S>) -> Self {
+		self.iter()
+			.filter(|u| !tags.contains(&u.tag()))
+			.cloned()
+			.collect()
+	}
 
 	/// Sorts the collection by given function.
 	pub fn sort<T, F>(&mut self, f: F)
@@ -421,7 +646,7 @@ impl Units {
 	{
 		self.0.sort_by(cmp_by2(f));
 	}
-	/// Makes new collection sorted by given function.
+	/// Makes new collection sorted by given function (i.e. sorted by key).
 	/// Leaves original collection untouched.
 	pub fn sorted<T, F>(&self, f: F) -> Self
 	where
@@ -432,6 +657,28 @@ impl Units {
 		sorted.0.sort_by(cmp_by2(f));
 		sorted
 	}
+	/// Makes new collection sorted with given comparator function.
+	/// Leaves original collection untouched.
+	pub fn sorted_by<F>(&self, mut compare: F) -> Self
+	where
+		F: FnMut(&Unit, &Unit) -> Ordering,
+	{
+		let mut sorted = self.clone();
+		sorted.0.sort_by(|_, a, _, b| compare(a, b));
+		sorted
+	}
+	/// Makes new collection sorted by distance to given target (closest first).
+	/// Leaves original collection untouched.
+	///
+	/// Distance to each unit is calculated only once.
+	pub fn sorted_by_distance<P: Into<Point2> + Copy>(&self, target: P) -> Self {
+		let mut units = self
+			.iter()
+			.map(|u| (u.distance_squared(target), u))
+			.collect::<Vec<_>>();
+		units.sort_by(|(a, _), (b, _)| cmp(a, b));
+		units.into_iter().map(|(_, u)| u.clone()).collect()
+	}
 }
 
 impl FromIterator<Unit> for Units {
@@ -544,7 +791,6 @@ where
 	move |_, a, _, b| f(a).partial_cmp(&f(b)).unwrap()
 }
 
-#[cfg(not(feature = "rayon"))]
 use crate::distance::Distance;
 #[cfg(not(feature = "rayon"))]
 use std::iter::Sum;
@@ -636,6 +882,39 @@ impl Units {
 		self.max_value(|u| u.distance_squared(target))
 	}
 
+	/// Returns closest pair of units from this and other collection and distance between them
+	/// (i.e. how far apart two groups are), or `None` if any of collections is empty.
+	///
+	/// Note: It checks every pair of units, so it's `O(n * m)`. For large groups it's better to
+	/// cluster them first and check only units on the edges of clusters.
+	pub fn closest_pair<'a>(&'a self, other: &'a Units) -> Option<(&'a Unit, &'a Unit, f32)> {
+		self.iter()
+			.flat_map(|u1| other.iter().map(move |u2| (u1, u2, u1.distance_squared(u2))))
+			.min_by(|(_, _, d1), (_, _, d2)| cmp(d1, d2))
+			.map(|(u1, u2, dist)| (u1, u2, dist.sqrt()))
+	}
+
+	/// Returns units located in the cone with apex at `from`, heading toward `toward`
+	/// and total opening angle of `cone_degrees`.
+	///
+	/// Unit is selected if the dot product of normalized heading (`toward - from`)
+	/// and normalized direction to unit (`unit - from`) is at least `cos(cone_degrees / 2)`,
+	/// e.g. `90.0` selects units within 45° to each side of the heading
+	/// and `360.0` selects everything.
+	/// Units exactly at `from` are never selected, neither anything is if `from == toward`.
+	pub fn in_direction_of(&self, from: Point2, toward: Point2, cone_degrees: f32) -> Self {
+		let heading = toward - from;
+		if heading.len_squared() == 0.0 {
+			return Self::new();
+		}
+		let heading = heading.normalize();
+		let min_dot = (cone_degrees.to_radians() / 2.0).cos();
+		self.filter(|u| {
+			let direction = u.position() - from;
+			direction.len_squared() > 0.0 && heading.dot(direction.normalize()) >= min_dot
+		})
+	}
+
 	/// Returns sum of given unit values.
 	pub fn sum<T, F>(&self, f: F) -> T
 	where
@@ -732,3 +1011,69 @@ impl<T: Eq + Hash, V> Container<T> for IndexMap<T, V> {
 		self.contains_key(item)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::unit::tests::{make_unit, proto_unit, unit};
+	use iter::UnitsIterator;
+	use sc2_proto::raw::DisplayType as ProtoDisplayType;
+
+	#[test]
+	fn of_type_including_alias() {
+		let units = vec![
+			unit(1, UnitTypeId::SiegeTank),
+			unit(2, UnitTypeId::SiegeTankSieged),
+			unit(3, UnitTypeId::WidowMine),
+			unit(4, UnitTypeId::WidowMineBurrowed),
+			unit(5, UnitTypeId::Marine),
+		]
+		.into_iter()
+		.collect::<Units>();
+
+		let tanks = units.of_type_including_alias(UnitTypeId::SiegeTank);
+		assert_eq!(tanks.len(), 2);
+		assert!(tanks.contains_tag(1) && tanks.contains_tag(2));
+		assert_eq!(
+			units.of_type_including_alias(UnitTypeId::SiegeTankSieged).len(),
+			2
+		);
+
+		let mines = units.of_type_including_alias(UnitTypeId::WidowMine);
+		assert_eq!(mines.len(), 2);
+		assert!(mines.contains_tag(3) && mines.contains_tag(4));
+
+		assert_eq!(units.of_type(UnitTypeId::SiegeTank).len(), 1);
+	}
+
+	#[test]
+	fn filter_by_display_type() {
+		let with_display_type = |tag, display_type| {
+			let mut u = proto_unit(tag, UnitTypeId::Stalker, 10.0, 10.0);
+			u.set_display_type(display_type);
+			make_unit(&Default::default(), &u)
+		};
+		let units = vec![
+			with_display_type(1, ProtoDisplayType::Visible),
+			with_display_type(2, ProtoDisplayType::Snapshot),
+			with_display_type(3, ProtoDisplayType::Visible),
+			with_display_type(4, ProtoDisplayType::Snapshot),
+			with_display_type(5, ProtoDisplayType::Hidden),
+		]
+		.into_iter()
+		.collect::<Units>();
+
+		let visible = units.visible();
+		assert_eq!(visible.tags().copied().collect::<Vec<_>>(), vec![1, 3]);
+		assert!(visible.iter().all(|u| u.is_visible()));
+
+		let snapshots = units.snapshots();
+		assert_eq!(snapshots.tags().copied().collect::<Vec<_>>(), vec![2, 4]);
+		assert!(snapshots.iter().all(|u| u.is_snapshot()));
+
+		assert_eq!(units.hidden().tags().copied().collect::<Vec<_>>(), vec![5]);
+		assert_eq!(units.iter().visible().count(), 2);
+		assert_eq!(units.iter().snapshots().count(), 2);
+		assert_eq!(units.iter().hidden().count(), 1);
+	}
+}
@@ -2,7 +2,11 @@
 
 use indexmap::IndexSet;
 use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
-use std::hash::{BuildHasherDefault, Hash};
+use std::{
+	cmp::Ordering,
+	collections::BinaryHeap,
+	hash::{BuildHasherDefault, Hash},
+};
 
 type FxIndexSet<T> = IndexSet<T, BuildHasherDefault<FxHasher>>;
 
@@ -80,6 +84,127 @@ where
 	}
 }
 
+#[derive(PartialEq)]
+struct Node {
+	f: f32,
+	pos: (usize, usize),
+}
+impl Eq for Node {}
+impl PartialOrd for Node {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for Node {
+	// Reversed to make `BinaryHeap` pop node with the lowest cost first
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.f.partial_cmp(&self.f).unwrap_or(Ordering::Equal)
+	}
+}
+
+const NEIGHBORS: [(isize, isize); 8] = [
+	(1, 0),
+	(-1, 0),
+	(0, 1),
+	(0, -1),
+	(1, 1),
+	(-1, -1),
+	(1, -1),
+	(-1, 1),
+];
+
+fn octile((x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> f32 {
+	let dx = (x0 as f32 - x1 as f32).abs();
+	let dy = (y0 as f32 - y1 as f32).abs();
+	dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+}
+
+/// A* pathfinding implementation on 2D grid.
+///
+/// Inputs:
+/// - `start` and `goal`: tile positions of path ends.
+/// - `cost`: function that should return cost of stepping on given tile
+///   or `None` if tile isn't passable (cost should be at least `1` for heuristic to stay admissible).
+/// - `diagonal`: allows diagonal moves (cutting corners of obstacles isn't allowed).
+///
+/// Uses octile distance as heuristic.
+///
+/// Returns: tiles of the path from `start` to `goal` (both included) or `None` if there's no path.
+pub fn astar<F>(
+	start: (usize, usize),
+	goal: (usize, usize),
+	cost: F,
+	diagonal: bool,
+) -> Option<Vec<(usize, usize)>>
+where
+	F: Fn((usize, usize)) -> Option<f32>,
+{
+	cost(goal)?;
+
+	let mut open = BinaryHeap::new();
+	let mut g = FxHashMap::<(usize, usize), f32>::default();
+	let mut came_from = FxHashMap::<(usize, usize), (usize, usize)>::default();
+	g.insert(start, 0.0);
+	open.push(Node {
+		f: octile(start, goal),
+		pos: start,
+	});
+
+	while let Some(Node { f, pos }) = open.pop() {
+		if pos == goal {
+			let mut path = vec![goal];
+			let mut current = goal;
+			while let Some(&prev) = came_from.get(&current) {
+				path.push(prev);
+				current = prev;
+			}
+			path.reverse();
+			return Some(path);
+		}
+		let current_g = g[&pos];
+		if f > current_g + octile(pos, goal) {
+			// Outdated node, better one was already processed
+			continue;
+		}
+
+		let (x, y) = pos;
+		for &(dx, dy) in &NEIGHBORS {
+			let is_diagonal = dx != 0 && dy != 0;
+			if is_diagonal && !diagonal {
+				continue;
+			}
+			let (nx, ny) = (x as isize + dx, y as isize + dy);
+			if nx < 0 || ny < 0 {
+				continue;
+			}
+			let next = (nx as usize, ny as usize);
+			let step_cost = match cost(next) {
+				Some(c) => c,
+				None => continue,
+			};
+			if is_diagonal && (cost((next.0, y)).is_none() || cost((x, next.1)).is_none()) {
+				continue;
+			}
+
+			let distance = if is_diagonal {
+				std::f32::consts::SQRT_2
+			} else {
+				1.0
+			};
+			let next_g = current_g + step_cost * distance;
+			if !matches!(g.get(&next), Some(&old) if old <= next_g) {
+				g.insert(next, next_g);
+				came_from.insert(next, pos);
+				open.push(Node {
+					f: next_g + octile(next, goal),
+					pos: next,
+				});
+			}
+		}
+	}
+	None
+}
+
 #[cfg(feature = "parking_lot")]
 use parking_lot::{RwLock, RwLockReadGuard};
 #[cfg(not(feature = "parking_lot"))]
@@ -125,3 +250,44 @@ where
 		read(&self.0).get(k).copied()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn astar_goes_around_obstacle() {
+		// 5x5 grid with a wall at x = 2, leaving only the top tile free
+		let cost = |(x, y): (usize, usize)| {
+			if x >= 5 || y >= 5 || (x == 2 && y < 4) {
+				None
+			} else {
+				Some(1.0)
+			}
+		};
+
+		for diagonal in [false, true] {
+			let path = astar((0, 0), (4, 0), cost, diagonal).expect("path should be found");
+			assert_eq!(path.first(), Some(&(0, 0)));
+			assert_eq!(path.last(), Some(&(4, 0)));
+			assert!(path.contains(&(2, 4)));
+			assert!(path.iter().all(|&pos| cost(pos).is_some()));
+			for pair in path.windows(2) {
+				let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+				assert!(x0.abs_diff(x1) <= 1 && y0.abs_diff(y1) <= 1);
+			}
+		}
+	}
+
+	#[test]
+	fn astar_no_path() {
+		let cost = |(x, y): (usize, usize)| {
+			if x >= 5 || y >= 5 || x == 2 {
+				None
+			} else {
+				Some(1.0)
+			}
+		};
+		assert_eq!(astar((0, 0), (4, 0), cost, true), None);
+	}
+}